@@ -1,24 +1,86 @@
 use anyhow::Context;
-use clap::Parser;
-use regex::Regex;
-use squidge::{Config, shorten_line};
+use clap::{Parser, ValueEnum};
+use squidge::{
+    Collapse, Config, Elision, EmptyComponents, Engine, Node, Quoting, Rule, Selector, Strategy,
+    Tree, escape_bytes, shorten_line_into, shorten_lines_unique, split_common_prefix,
+    unescape_bytes,
+};
+use std::borrow::Cow;
 use std::fs::File;
-use std::io::{self, BufRead, BufReader};
+use std::io::{self, BufRead, BufReader, Write};
+use std::str::FromStr;
+use terminal_size::{Width, terminal_size};
 
 const DEFAULT_DELIMITER: &str = "/";
 const DEFAULT_IGNORE_FIRST_N: usize = 0;
 const DEFAULT_IGNORE_LAST_N: usize = 1;
+const DEFAULT_STRATEGY: &str = "first";
+const DEFAULT_COLLAPSE: &str = "off";
+const DEFAULT_EMPTY_COMPONENTS: &str = "keep";
+const ALIAS_SEPARATOR: &str = " => ";
+const HOME_TOKEN: &str = "~";
+const COMMON_PREFIX_MARKER: &str = "…";
+const DEFAULT_ELISION_MARKER: &str = "…";
 
 #[derive(Parser, Debug)]
 #[command(about, long_about=None)]
 struct Args {
-    /// Delimiter
+    /// Delimiter (can be provided multiple times)
     #[arg(short = 'd', long = "delimiter", value_name = "STRING")]
     #[clap(default_value = DEFAULT_DELIMITER)]
-    delimiter: String,
+    delimiter: Vec<String>,
+    /// Regex to split lines on (overrides --delimiter)
+    #[arg(
+        short = 'D',
+        long = "delimiter-regex",
+        value_name = "STRING",
+        conflicts_with = "delimiter"
+    )]
+    delimiter_regex: Option<String>,
     /// Regex for ignoring elements (ie, they won't be shortened)
     #[arg(short = 'r', long = "ignore-regex", value_name = "STRING")]
     ignore_regex: Option<String>,
+    /// Keep elements at these indices as is, using Python-like indices and slices (eg, "2,-2:")
+    #[arg(short = 'K', long = "keep", value_name = "INDICES")]
+    keep: Option<Selector>,
+    /// What to do with empty elements (eg, the one in "a//b") [possible values: keep, drop, placeholder:TEXT]
+    #[arg(short = 'E', long = "empty-components", value_name = "POLICY")]
+    #[clap(default_value = DEFAULT_EMPTY_COMPONENTS)]
+    empty_components: EmptyComponents,
+    /// Preserve the leading delimiter of absolute lines (eg, "/usr/bin") without counting it as an element
+    #[arg(short = 'A', long = "absolute-root", value_name = "BOOLEAN")]
+    absolute_root: bool,
+    /// Regex with capture groups for extracting parts of elements while shortening them
+    #[arg(
+        short = 'e',
+        long = "extract-regex",
+        value_name = "STRING",
+        requires = "extract_template"
+    )]
+    extract_regex: Option<String>,
+    /// Template that elements matching the extract regex are replaced with (eg, "$1$2")
+    #[arg(
+        short = 't',
+        long = "extract-template",
+        value_name = "STRING",
+        requires = "extract_regex"
+    )]
+    extract_template: Option<String>,
+    /// Regex engine for the delimiter, ignore and extract regexes [possible values: regex, regex-automata, regex-lite, fancy-regex (if built with them)] [default: regex]
+    #[arg(long = "regex-engine", value_name = "ENGINE")]
+    regex_engine: Option<Engine>,
+    /// File with rules for handling elements (one "<matcher> => <action>" per line)
+    #[arg(short = 'R', long = "rules-path", value_name = "STRING")]
+    rules_file_path: Option<String>,
+    /// File with aliases for sequences of elements (one "<sequence> => <alias>" per line)
+    #[arg(short = 'a', long = "aliases-path", value_name = "STRING")]
+    aliases_file_path: Option<String>,
+    /// Replace the home directory ($HOME) at the start of lines with "~"
+    #[arg(short = 'H', long = "substitute-home", value_name = "BOOLEAN")]
+    substitute_home: bool,
+    /// Environment variable whose value is replaced with "$NAME" at the start of lines (can be provided multiple times)
+    #[arg(short = 'v', long = "prefix-var", value_name = "NAME")]
+    prefix_vars: Vec<String>,
     /// Input file
     #[arg(short = 'p', long = "input-path", value_name = "STRING")]
     input_file_path: Option<String>,
@@ -34,27 +96,209 @@ struct Args {
     #[arg(short = 'o', long = "output-delimiter", value_name = "STRING")]
     #[clap(default_value = DEFAULT_DELIMITER)]
     output_delimiter: String,
+    /// Join elements with the delimiters they were originally separated by
+    #[arg(
+        short = 'k',
+        long = "keep-delimiters",
+        value_name = "BOOLEAN",
+        conflicts_with_all = ["output_delimiter", "unique"]
+    )]
+    keep_delimiters: bool,
+    /// Don't split elements quoted with this character, and quote output elements containing the output delimiter
+    #[arg(short = 'q', long = "quote", value_name = "CHAR")]
+    quote: Option<char>,
+    /// Character escaping quotes within quoted elements [default: the quote character]
+    #[arg(
+        short = 'Q',
+        long = "quote-escape",
+        value_name = "CHAR",
+        requires = "quote"
+    )]
+    quote_escape: Option<char>,
     /// Read input from stdin
     #[arg(short = 's', long = "use-stdin", value_name = "BOOLEAN")]
     use_stdin: bool,
+    /// Abbreviation strategy [possible values: first, first:N, last, last:N, first-last]
+    #[arg(short = 'S', long = "strategy", value_name = "STRATEGY")]
+    #[clap(default_value = DEFAULT_STRATEGY)]
+    strategy: Strategy,
+    /// Keep file extensions (eg, ".tar.gz") and leading dots as is while shortening elements
+    #[arg(short = 'F', long = "filename-aware", value_name = "BOOLEAN")]
+    filename_aware: bool,
+    /// Collapse repeated elements, or blocks of them, into one (eg, "b/b/b" -> "b×3") [possible values: off, runs, blocks]
+    #[arg(short = 'C', long = "collapse", value_name = "MODE")]
+    #[clap(default_value = DEFAULT_COLLAPSE)]
+    collapse: Collapse,
+    /// Only keep these many elements from the start and end of lines, replacing the ones in between with a marker (eg, "1:2")
+    #[arg(short = 'x', long = "elide", value_name = "FIRST:LAST")]
+    elide: Option<KeptEnds>,
+    /// Marker that elided elements are replaced with ("{count}" is replaced with their number)
+    #[arg(short = 'X', long = "elision-marker", value_name = "STRING")]
+    #[clap(default_value = DEFAULT_ELISION_MARKER)]
+    elision_marker: String,
+    /// Shorten elements to the shortest prefix that's unique among their siblings
+    #[arg(short = 'u', long = "unique", value_name = "BOOLEAN")]
+    unique: bool,
+    /// Print the prefix shared by all lines once as a header, or replace it with a marker ("…")
+    #[arg(short = 'c', long = "common-prefix", value_name = "MODE")]
+    common_prefix: Option<CommonPrefixMode>,
+    /// Print the elements of all lines as a tree (interior elements are shortened, leaves aren't)
+    #[arg(
+        short = 'T',
+        long = "tree",
+        value_name = "BOOLEAN",
+        conflicts_with_all = ["unique", "common_prefix", "keep_delimiters"]
+    )]
+    tree: bool,
+    /// Print the tree as a Graphviz (DOT) graph
+    #[arg(long = "dot", value_name = "BOOLEAN", requires = "tree")]
+    dot: bool,
+    /// Only shorten lines as much as needed to fit within these many columns ("auto" uses the terminal's width)
+    #[arg(short = 'w', long = "max-width", value_name = "COLUMNS|auto")]
+    max_width: Option<MaxWidth>,
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum CommonPrefixMode {
+    Header,
+    Marker,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum MaxWidth {
+    Columns(usize),
+    Auto,
+}
+
+impl FromStr for MaxWidth {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "auto" => Ok(MaxWidth::Auto),
+            _ => s
+                .parse::<usize>()
+                .map(MaxWidth::Columns)
+                .map_err(|_| r#"expected a number or "auto""#.to_string()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct KeptEnds {
+    first: usize,
+    last: usize,
+}
+
+impl FromStr for KeptEnds {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (first, last) = s
+            .split_once(':')
+            .ok_or_else(|| r#"expected "FIRST:LAST" (eg, "1:2")"#.to_string())?;
+        let parse = |n: &str| {
+            n.parse::<usize>()
+                .map_err(|_| format!(r#"expected a number, got "{n}""#))
+        };
+
+        Ok(KeptEnds {
+            first: parse(first)?,
+            last: parse(last)?,
+        })
+    }
+}
+
+impl MaxWidth {
+    fn resolve(self) -> Option<usize> {
+        match self {
+            MaxWidth::Columns(n) => Some(n),
+            MaxWidth::Auto => terminal_size()
+                .map(|(Width(w), _)| usize::from(w))
+                .or_else(|| std::env::var("COLUMNS").ok()?.parse().ok()),
+        }
+    }
 }
 
 fn main() -> anyhow::Result<()> {
     let args = Args::parse();
 
-    let re = args
-        .ignore_regex
-        .map(|r| Regex::new(&r).context("couldn't compile regex"))
-        .transpose()?;
+    let mut builder = Config::builder()
+        .engine(args.regex_engine.unwrap_or_default())
+        .output_delimiter(args.output_delimiter)
+        .keep_delimiters(args.keep_delimiters)
+        .ignore_first_n(args.ignore_first_n)
+        .ignore_last_n(args.ignore_last_n)
+        .empty_components(args.empty_components)
+        .absolute_root(args.absolute_root)
+        .strategy(args.strategy)
+        .filename_aware(args.filename_aware)
+        .collapse(args.collapse);
 
-    let cfg = Config {
-        delimiter: &args.delimiter,
-        ignore_first_n: args.ignore_first_n,
-        ignore_last_n: args.ignore_last_n,
-        ignore_regex: re,
+    builder = match args.delimiter_regex {
+        Some(r) => builder.delimiter_regex(r),
+        None => match args.delimiter.as_slice() {
+            [d] => builder.delimiter(d.as_str()),
+            ds => builder.delimiters(ds.iter().map(String::as_str)),
+        },
     };
 
-    let lines = match (args.use_stdin, args.input_file_path) {
+    if let Some(r) = args.ignore_regex {
+        builder = builder.ignore_regex(r);
+    }
+
+    if let Some(selector) = args.keep {
+        builder = builder.ignore_indices(selector);
+    }
+
+    if let (Some(r), Some(t)) = (args.extract_regex, args.extract_template) {
+        builder = builder.extraction(r, t);
+    }
+
+    if args.substitute_home
+        && let Some(home) = env_prefix("HOME")
+    {
+        builder = builder.prefix(home, HOME_TOKEN);
+    }
+
+    for name in &args.prefix_vars {
+        if let Some(value) = env_prefix(name) {
+            builder = builder.prefix(value, format!("${name}"));
+        }
+    }
+
+    if let Some(path) = args.aliases_file_path {
+        for (sequence, token) in read_aliases(&path)? {
+            builder = builder.alias(sequence, token);
+        }
+    }
+
+    if let Some(path) = args.rules_file_path {
+        builder = builder.rules(read_rules(&path)?);
+    }
+
+    if let Some(quote) = args.quote {
+        builder = builder.quoting(Quoting {
+            quote,
+            escape: args.quote_escape.unwrap_or(quote),
+        });
+    }
+
+    if let Some(ends) = args.elide {
+        builder = builder.elision(Elision {
+            keep_first: ends.first,
+            keep_last: ends.last,
+            marker: args.elision_marker,
+        });
+    }
+
+    if let Some(w) = args.max_width.and_then(MaxWidth::resolve) {
+        builder = builder.max_width(w);
+    }
+
+    let cfg = builder.build()?;
+
+    let (lines, escaped) = match (args.use_stdin, args.input_file_path) {
         (false, None) => {
             return Err(anyhow::anyhow!(
                 "a source needs to be provided (either a file or stdin)"
@@ -65,23 +309,10 @@ fn main() -> anyhow::Result<()> {
                 "only one source (either a file or stdin) can be used at a time"
             ));
         }
-        (true, None) => {
-            let stdin = io::stdin();
-            let mut lines = vec![];
-            for line in stdin.lock().lines() {
-                let line = line.context("couldn't read line from stdin")?;
-                lines.push(line);
-            }
-            lines
-        }
+        (true, None) => read_lines(io::stdin().lock()).context("couldn't read line from stdin")?,
         (false, Some(path)) => {
             let file = File::open(path)?;
-            let reader = BufReader::new(file);
-
-            reader
-                .lines()
-                .map(|line| line.context("couldn't read line from file"))
-                .collect::<Result<_, _>>()?
+            read_lines(BufReader::new(file)).context("couldn't read line from file")?
         }
     };
 
@@ -89,26 +320,183 @@ fn main() -> anyhow::Result<()> {
         return Err(anyhow::anyhow!("nothing to shorten"));
     }
 
-    let shortened_lines = get_shortened_lines(&cfg, &lines, &args.output_delimiter);
+    if args.tree {
+        let tree = Tree::new(&cfg, &lines);
+        print_tree(&tree, args.dot, &escaped)?;
 
-    println!("{}", shortened_lines.join("\n"));
+        return Ok(());
+    }
+
+    let common = args
+        .common_prefix
+        .map(|mode| (mode, split_common_prefix(&cfg, &lines)));
+    let lines = match &common {
+        Some((_, common)) => common.remainders.clone(),
+        None => lines.iter().map(String::as_str).collect(),
+    };
+
+    let mut shortened_lines = if args.unique {
+        get_uniquely_shortened_lines(&cfg, &lines)
+    } else {
+        get_shortened_lines(&cfg, &lines)
+    };
+
+    if let Some((mode, common)) = common
+        && let Some(delimiter) = common.delimiter
+    {
+        let delimiter = if cfg.keep_delimiters {
+            delimiter
+        } else {
+            &cfg.output_delimiter
+        };
+        match mode {
+            // the prefix is taken from the first line
+            CommonPrefixMode::Header => {
+                print_line(&format!("{}{delimiter}", common.prefix), escaped[0])?
+            }
+            CommonPrefixMode::Marker => {
+                let marker = format!("{COMMON_PREFIX_MARKER}{delimiter}");
+                for line in &mut shortened_lines {
+                    line.insert_str(0, &marker);
+                }
+            }
+        }
+    }
+
+    for (line, escaped) in shortened_lines.iter().zip(escaped) {
+        print_line(line, escaped)?;
+    }
 
     Ok(())
 }
 
-fn get_shortened_lines(cfg: &Config, lines: &[String], output_delimiter: &str) -> Vec<String> {
+/// Reads lines as raw bytes, without their line endings; bytes that aren't valid UTF-8 are
+/// escaped, so they can be passed through unchanged when printed. Returns the lines, along with
+/// whether each of them had bytes escaped.
+fn read_lines(reader: impl BufRead) -> io::Result<(Vec<String>, Vec<bool>)> {
+    reader
+        .split(b'\n')
+        .map(|line| {
+            let mut line = line?;
+            if line.last() == Some(&b'\r') {
+                line.pop();
+            }
+            Ok(match escape_bytes(&line) {
+                Cow::Borrowed(text) => (text.to_string(), false),
+                Cow::Owned(text) => (text, true),
+            })
+        })
+        .collect()
+}
+
+/// Prints a line, restoring the bytes that were escaped when it was read, if any were.
+fn print_line(line: &str, escaped: bool) -> io::Result<()> {
+    let mut stdout = io::stdout().lock();
+    if escaped {
+        stdout.write_all(&unescape_bytes(line))?;
+    } else {
+        stdout.write_all(line.as_bytes())?;
+    }
+    stdout.write_all(b"\n")
+}
+
+/// Prints a tree, restoring the bytes that were escaped in the lines its nodes come from.
+fn print_tree(tree: &Tree, dot: bool, escaped: &[bool]) -> io::Result<()> {
+    fn push_nodes<'t>(nodes: &'t [Node], all: &mut Vec<&'t Node>) {
+        for node in nodes {
+            all.push(node);
+            push_nodes(node.children(), all);
+        }
+    }
+
+    let mut nodes = vec![];
+    push_nodes(tree.roots(), &mut nodes);
+    let mut nodes = nodes.into_iter();
+
+    // nodes are rendered on lines of their own, in the order they were pushed in; the other lines
+    // of DOT graphs (edges and braces) have nothing to restore
+    let rendered = if dot { tree.to_dot() } else { tree.to_string() };
+    for line in rendered.lines() {
+        let is_node = !dot || line.ends_with("\"];");
+        let escaped = is_node && nodes.next().is_some_and(|node| escaped[node.line()]);
+        print_line(line, escaped)?;
+    }
+
+    Ok(())
+}
+
+/// Returns the value of an environment variable, without trailing path separators, for use as a
+/// prefix; unset and empty variables are skipped.
+fn env_prefix(name: &str) -> Option<String> {
+    let value = std::env::var(name).ok()?;
+    let trimmed = value.trim_end_matches(['/', std::path::MAIN_SEPARATOR]);
+
+    (!trimmed.is_empty()).then(|| trimmed.to_string())
+}
+
+/// Reads rules from a file, one per line.
+fn read_rules(path: &str) -> anyhow::Result<Vec<Rule>> {
+    read_entries(path, "rules")?
+        .into_iter()
+        .map(|(number, line)| {
+            line.parse::<Rule>()
+                .with_context(|| format!("couldn't parse rule on line {number}"))
+        })
+        .collect()
+}
+
+/// Reads aliases from a file, one "<sequence> => <alias>" per line.
+fn read_aliases(path: &str) -> anyhow::Result<Vec<(String, String)>> {
+    read_entries(path, "aliases")?
+        .into_iter()
+        .map(|(number, line)| {
+            line.trim()
+                .rsplit_once(ALIAS_SEPARATOR)
+                .map(|(sequence, token)| (sequence.to_string(), token.to_string()))
+                .ok_or_else(|| anyhow::anyhow!(r#"expected "<sequence>{ALIAS_SEPARATOR}<alias>""#))
+                .with_context(|| format!("couldn't parse alias on line {number}"))
+        })
+        .collect()
+}
+
+/// Reads the lines of a file, along with their line numbers; empty lines and lines starting with
+/// "#" are skipped.
+fn read_entries(path: &str, kind: &str) -> anyhow::Result<Vec<(usize, String)>> {
+    let contents = std::fs::read_to_string(path)
+        .with_context(|| format!("couldn't read {kind} file {path}"))?;
+
+    Ok(contents
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty() && !line.trim_start().starts_with('#'))
+        .map(|(i, line)| (i + 1, line.to_string()))
+        .collect())
+}
+
+fn get_shortened_lines<L: AsRef<str>>(cfg: &Config, lines: &[L]) -> Vec<String> {
     lines
         .iter()
         .map(|l| {
-            let s = shorten_line(cfg, l);
-            s.join(output_delimiter)
+            let l = l.as_ref();
+            let mut s = String::with_capacity(l.len());
+            // writing to a String never fails
+            let _ = shorten_line_into(cfg, l, &mut s);
+            s
         })
         .collect()
 }
 
+fn get_uniquely_shortened_lines<L: AsRef<str>>(cfg: &Config, lines: &[L]) -> Vec<String> {
+    shorten_lines_unique(cfg, lines)
+        .into_iter()
+        .map(|components| components.join(&cfg.output_delimiter))
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use squidge::Regex;
 
     #[test]
     fn get_shortened_lines_works() {
@@ -120,11 +508,12 @@ mod tests {
             ignore_first_n: 2,
             ignore_last_n: 2,
             ignore_regex: Some(re),
+            output_delimiter: " .. ".to_string(),
             ..Config::default()
         };
 
         // WHEN
-        let shortened_lines = get_shortened_lines(&cfg, &lines, " .. ");
+        let shortened_lines = get_shortened_lines(&cfg, &lines);
 
         // THEN
         let expected = vec![
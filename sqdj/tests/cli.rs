@@ -12,7 +12,7 @@ fn shows_help() {
     let mut cmd = fx.cmd(["--help"]);
 
     // THEN
-    assert_cmd_snapshot!(cmd, @r"
+    assert_cmd_snapshot!(cmd, @r#"
     success: true
     exit_code: 0
     ----- stdout -----
@@ -21,17 +21,41 @@ fn shows_help() {
     Usage: sqdj [OPTIONS]
 
     Options:
-      -d, --delimiter <STRING>         Delimiter [default: /]
+      -d, --delimiter <STRING>         Delimiter (can be provided multiple times) [default: /]
+      -D, --delimiter-regex <STRING>   Regex to split lines on (overrides --delimiter)
       -r, --ignore-regex <STRING>      Regex for ignoring elements (ie, they won't be shortened)
+      -K, --keep <INDICES>             Keep elements at these indices as is, using Python-like indices and slices (eg, "2,-2:")
+      -E, --empty-components <POLICY>  What to do with empty elements (eg, the one in "a//b") [possible values: keep, drop, placeholder:TEXT] [default: keep]
+      -A, --absolute-root              Preserve the leading delimiter of absolute lines (eg, "/usr/bin") without counting it as an element
+      -e, --extract-regex <STRING>     Regex with capture groups for extracting parts of elements while shortening them
+      -t, --extract-template <STRING>  Template that elements matching the extract regex are replaced with (eg, "$1$2")
+          --regex-engine <ENGINE>      Regex engine for the delimiter, ignore and extract regexes [possible values: regex, regex-automata, regex-lite, fancy-regex (if built with them)] [default: regex]
+      -R, --rules-path <STRING>        File with rules for handling elements (one "<matcher> => <action>" per line)
+      -a, --aliases-path <STRING>      File with aliases for sequences of elements (one "<sequence> => <alias>" per line)
+      -H, --substitute-home            Replace the home directory ($HOME) at the start of lines with "~"
+      -v, --prefix-var <NAME>          Environment variable whose value is replaced with "$NAME" at the start of lines (can be provided multiple times)
       -p, --input-path <STRING>        Input file
       -f, --ignore-first-n <NUMBER>    Ignore first n elements [default: 0]
       -l, --ignore-last-n <NUMBER>     Ignore last n elements [default: 1]
       -o, --output-delimiter <STRING>  Output delimiter [default: /]
+      -k, --keep-delimiters            Join elements with the delimiters they were originally separated by
+      -q, --quote <CHAR>               Don't split elements quoted with this character, and quote output elements containing the output delimiter
+      -Q, --quote-escape <CHAR>        Character escaping quotes within quoted elements [default: the quote character]
       -s, --use-stdin                  Read input from stdin
+      -S, --strategy <STRATEGY>        Abbreviation strategy [possible values: first, first:N, last, last:N, first-last] [default: first]
+      -F, --filename-aware             Keep file extensions (eg, ".tar.gz") and leading dots as is while shortening elements
+      -C, --collapse <MODE>            Collapse repeated elements, or blocks of them, into one (eg, "b/b/b" -> "b×3") [possible values: off, runs, blocks] [default: off]
+      -x, --elide <FIRST:LAST>         Only keep these many elements from the start and end of lines, replacing the ones in between with a marker (eg, "1:2")
+      -X, --elision-marker <STRING>    Marker that elided elements are replaced with ("{count}" is replaced with their number) [default: …]
+      -u, --unique                     Shorten elements to the shortest prefix that's unique among their siblings
+      -c, --common-prefix <MODE>       Print the prefix shared by all lines once as a header, or replace it with a marker ("…") [possible values: header, marker]
+      -T, --tree                       Print the elements of all lines as a tree (interior elements are shortened, leaves aren't)
+          --dot                        Print the tree as a Graphviz (DOT) graph
+      -w, --max-width <COLUMNS|auto>   Only shorten lines as much as needed to fit within these many columns ("auto" uses the terminal's width)
       -h, --help                       Print help
 
     ----- stderr -----
-    ");
+    "#);
 }
 
 #[test]
@@ -179,153 +203,1217 @@ fn uses_output_delimiter_correctly() {
     ");
 }
 
-// FAILURES
 #[test]
-fn fails_if_no_source_is_provided() {
+fn uses_strategy_correctly() {
     // GIVEN
     // WHEN
     let fx = Fixture::new();
-    let mut cmd = fx.base_cmd();
+    let mut cmd = fx.cmd([
+        "--input-path",
+        "tests/data/input-1.txt",
+        "--strategy",
+        "first:2",
+    ]);
 
     // THEN
     assert_cmd_snapshot!(cmd, @r"
-    success: false
-    exit_code: 1
+    success: true
+    exit_code: 0
     ----- stdout -----
+    sr/ma/sc/ad/bi/ApplicationComponents.scala
+    sr/ma/sc/ad/bi/Components.scala
+    sr/ma/sc/ad/bi/Server.scala
 
     ----- stderr -----
-    Error: a source needs to be provided (either a file or stdin)
     ");
 }
 
 #[test]
-fn fails_if_more_than_one_source_is_provided() {
+fn uses_unique_prefixes_correctly() {
     // GIVEN
     // WHEN
     let fx = Fixture::new();
-    let mut cmd = fx.cmd(["--input-path", "tests/data/input-1.txt", "--use-stdin"]);
+    let mut cmd = fx.cmd(["--input-path", "tests/data/input-3.txt", "--unique"]);
 
     // THEN
     assert_cmd_snapshot!(cmd, @r"
-    success: false
-    exit_code: 1
+    success: true
+    exit_code: 0
     ----- stdout -----
+    s/m/s/a/bi/Server.scala
+    s/m/s/a/ba/Server.scala
+    s/m/s/a/ba/Components.scala
+    s/t/s/a/b/ServerSpec.scala
 
     ----- stderr -----
-    Error: only one source (either a file or stdin) can be used at a time
     ");
 }
 
 #[test]
-fn fails_if_input_file_is_non_existent() {
+fn uses_max_width_correctly() {
     // GIVEN
     // WHEN
     let fx = Fixture::new();
-    let mut cmd = fx.cmd(["--input-path", "tests/data/nonexistent.txt"]);
+    let mut cmd = fx.cmd([
+        "--input-path",
+        "tests/data/input-1.txt",
+        "--max-width",
+        "40",
+    ]);
 
     // THEN
     assert_cmd_snapshot!(cmd, @r"
-    success: false
-    exit_code: 1
+    success: true
+    exit_code: 0
     ----- stdout -----
+    s/m/s/a/b/ApplicationComponents.scala
+    s/m/scala/admin/billing/Components.scala
+    s/main/scala/admin/billing/Server.scala
 
     ----- stderr -----
-    Error: No such file or directory (os error 2)
     ");
 }
 
 #[test]
-fn fails_if_input_file_is_empty() {
+fn uses_terminal_width_for_auto_max_width() {
     // GIVEN
     // WHEN
     let fx = Fixture::new();
-    let mut cmd = fx.cmd(["--input-path", "tests/data/empty.txt"]);
+    let mut cmd = fx.cmd([
+        "--input-path",
+        "tests/data/input-1.txt",
+        "--max-width",
+        "auto",
+    ]);
+    cmd.env("COLUMNS", "45");
 
     // THEN
     assert_cmd_snapshot!(cmd, @r"
-    success: false
-    exit_code: 1
+    success: true
+    exit_code: 0
     ----- stdout -----
+    s/m/s/a/billing/ApplicationComponents.scala
+    src/main/scala/admin/billing/Components.scala
+    src/main/scala/admin/billing/Server.scala
 
     ----- stderr -----
-    Error: nothing to shorten
     ");
 }
 
 #[test]
-fn fails_if_ignore_regex_is_incorrect() {
+fn works_for_multiple_delimiters() {
+    // GIVEN
+    // WHEN
+    let fx = Fixture::new();
+    let mut cmd = fx.cmd([
+        "--input-path",
+        "tests/data/input-4.txt",
+        "--delimiter",
+        "/",
+        "--delimiter",
+        ".",
+        "--delimiter",
+        "::",
+    ]);
+
+    // THEN
+    assert_cmd_snapshot!(cmd, @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    c/e/s/Handler
+    c/e/b/Server
+
+    ----- stderr -----
+    ");
+}
+
+#[test]
+fn works_for_delimiter_regex() {
+    // GIVEN
+    // WHEN
+    let fx = Fixture::new();
+    let mut cmd = fx.cmd([
+        "--input-path",
+        "tests/data/input-4.txt",
+        "--delimiter-regex",
+        "[/.]|::",
+    ]);
+
+    // THEN
+    assert_cmd_snapshot!(cmd, @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    c/e/s/Handler
+    c/e/b/Server
+
+    ----- stderr -----
+    ");
+}
+
+#[test]
+fn keeps_delimiters_correctly() {
+    // GIVEN
+    // WHEN
+    let fx = Fixture::new();
+    let mut cmd = fx.cmd([
+        "--input-path",
+        "tests/data/input-4.txt",
+        "--delimiter-regex",
+        "[/.]|::",
+        "--keep-delimiters",
+    ]);
+
+    // THEN
+    assert_cmd_snapshot!(cmd, @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    c.e/s::Handler
+    c.e/b::Server
+
+    ----- stderr -----
+    ");
+}
+
+#[cfg(feature = "fancy-regex")]
+#[test]
+fn uses_the_regex_engine_asked_for() {
     // GIVEN
     // WHEN
     let fx = Fixture::new();
     let mut cmd = fx.cmd([
         "--input-path",
         "tests/data/input-1.txt",
+        "--regex-engine",
+        "fancy-regex",
         "--ignore-regex",
-        "(?:scala|billing",
+        "^(?!billing)[a-z]+$",
     ]);
 
     // THEN
     assert_cmd_snapshot!(cmd, @r"
-    success: false
-    exit_code: 1
+    success: true
+    exit_code: 0
     ----- stdout -----
+    src/main/scala/admin/b/ApplicationComponents.scala
+    src/main/scala/admin/b/Components.scala
+    src/main/scala/admin/b/Server.scala
 
     ----- stderr -----
-    Error: couldn't compile regex
+    ");
+}
 
-    Caused by:
-        regex parse error:
-            (?:scala|billing
-            ^
-        error: unclosed group
+#[test]
+fn uses_rules_correctly() {
+    // GIVEN
+    // WHEN
+    let fx = Fixture::new();
+    let mut cmd = fx.cmd([
+        "--input-path",
+        "tests/data/input-3.txt",
+        "--rules-path",
+        "tests/data/rules-1.txt",
+    ]);
+
+    // THEN
+    assert_cmd_snapshot!(cmd, @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    m/sc/a/billing/S…a
+    m/sc/a/backup/S…a
+    m/sc/a/backup/C…a
+    t/sc/a/billing/S…a
+
+    ----- stderr -----
     ");
 }
 
 #[test]
-fn fails_if_ignore_first_n_is_not_a_number() {
+fn uses_aliases_correctly() {
     // GIVEN
     // WHEN
     let fx = Fixture::new();
     let mut cmd = fx.cmd([
         "--input-path",
-        "tests/data/input-1.txt",
-        "--ignore-first-n",
-        "blah",
+        "tests/data/input-3.txt",
+        "--aliases-path",
+        "tests/data/aliases-1.txt",
     ]);
 
     // THEN
     assert_cmd_snapshot!(cmd, @r"
-    success: false
-    exit_code: 2
+    success: true
+    exit_code: 0
     ----- stdout -----
+    main/a/b/Server.scala
+    main/a/b/Server.scala
+    main/a/b/Components.scala
+    test/a/b/ServerSpec.scala
 
     ----- stderr -----
-    error: invalid value 'blah' for '--ignore-first-n <NUMBER>': invalid digit found in string
+    ");
+}
 
-    For more information, try '--help'.
+#[test]
+fn substitutes_home_and_variable_prefixes() {
+    // GIVEN
+    // WHEN
+    let fx = Fixture::new();
+    let mut cmd = fx.cmd([
+        "--input-path",
+        "tests/data/input-6.txt",
+        "--substitute-home",
+        "--prefix-var",
+        "GOPATH",
+        "--prefix-var",
+        "UNSET_SQDJ_VAR",
+    ]);
+    cmd.env("HOME", "/home/alice")
+        .env("GOPATH", "/home/alice/work/go/")
+        .env_remove("UNSET_SQDJ_VAR");
+
+    // THEN
+    assert_cmd_snapshot!(cmd, @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    $GOPATH/s/g/d/s/main.go
+    ~/p/s/s/lib.rs
+    /h/a/p/s/s/lib.rs
+
+    ----- stderr -----
     ");
 }
 
 #[test]
-fn fails_if_ignore_last_n_is_not_a_number() {
+fn prints_common_prefix_as_header() {
     // GIVEN
     // WHEN
     let fx = Fixture::new();
     let mut cmd = fx.cmd([
         "--input-path",
-        "tests/data/input-1.txt",
-        "--ignore-last-n",
-        "blah",
+        "tests/data/input-3.txt",
+        "--common-prefix",
+        "header",
     ]);
 
     // THEN
     assert_cmd_snapshot!(cmd, @r"
-    success: false
-    exit_code: 2
+    success: true
+    exit_code: 0
     ----- stdout -----
+    src/
+    m/s/a/b/Server.scala
+    m/s/a/b/Server.scala
+    m/s/a/b/Components.scala
+    t/s/a/b/ServerSpec.scala
 
     ----- stderr -----
-    error: invalid value 'blah' for '--ignore-last-n <NUMBER>': invalid digit found in string
+    ");
+}
+
+#[test]
+fn replaces_common_prefix_with_marker() {
+    // GIVEN
+    // WHEN
+    let fx = Fixture::new();
+    let mut cmd = fx.cmd([
+        "--input-path",
+        "tests/data/input-2.txt",
+        "--delimiter",
+        "::",
+        "--common-prefix",
+        "marker",
+    ]);
+
+    // THEN
+    assert_cmd_snapshot!(cmd, @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    …/ApplicationComponents.scala
+    …/Components.scala
+    …/Server.scala
+
+    ----- stderr -----
+    ");
+}
+
+#[test]
+fn prints_tree_correctly() {
+    // GIVEN
+    // WHEN
+    let fx = Fixture::new();
+    let mut cmd = fx.cmd(["--input-path", "tests/data/input-3.txt", "--tree"]);
+
+    // THEN
+    assert_cmd_snapshot!(cmd, @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    s
+    ├── m
+    │   └── s
+    │       └── a
+    │           ├── b
+    │           │   └── Server.scala
+    │           └── b
+    │               ├── Server.scala
+    │               └── Components.scala
+    └── t
+        └── s
+            └── a
+                └── b
+                    └── ServerSpec.scala
+
+    ----- stderr -----
+    ");
+}
+
+#[test]
+fn prints_tree_as_dot_correctly() {
+    // GIVEN
+    // WHEN
+    let fx = Fixture::new();
+    let mut cmd = fx.cmd([
+        "--input-path",
+        "tests/data/input-2.txt",
+        "--delimiter",
+        "::",
+        "--ignore-first-n",
+        "3",
+        "--tree",
+        "--dot",
+    ]);
+
+    // THEN
+    assert_cmd_snapshot!(cmd, @r#"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    digraph {
+        n0 [label="src"];
+        n1 [label="main"];
+        n2 [label="scala"];
+        n3 [label="a"];
+        n4 [label="b"];
+        n5 [label="ApplicationComponents.scala"];
+        n4 -> n5;
+        n6 [label="Components.scala"];
+        n4 -> n6;
+        n7 [label="Server.scala"];
+        n4 -> n7;
+        n3 -> n4;
+        n2 -> n3;
+        n1 -> n2;
+        n0 -> n1;
+    }
+
+    ----- stderr -----
+    "#);
+}
+
+#[test]
+fn collapses_repeated_elements() {
+    // GIVEN
+    // WHEN
+    let fx = Fixture::new();
+    let mut cmd = fx.cmd([
+        "--input-path",
+        "tests/data/input-7.txt",
+        "--collapse",
+        "blocks",
+    ]);
+
+    // THEN
+    assert_cmd_snapshot!(cmd, @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    t/b×3/r/lib.rs
+    (c/e)×2/Foo.java
+
+    ----- stderr -----
+    ");
+}
+
+#[test]
+fn elides_middle_elements() {
+    // GIVEN
+    // WHEN
+    let fx = Fixture::new();
+    let mut cmd = fx.cmd([
+        "--input-path",
+        "tests/data/input-1.txt",
+        "--ignore-first-n",
+        "1",
+        "--elide",
+        "1:2",
+        "--elision-marker",
+        "…({count})",
+    ]);
+
+    // THEN
+    assert_cmd_snapshot!(cmd, @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    src/…(3)/b/ApplicationComponents.scala
+    src/…(3)/b/Components.scala
+    src/…(3)/b/Server.scala
+
+    ----- stderr -----
+    ");
+}
+
+#[test]
+fn drops_empty_elements_and_preserves_absolute_root() {
+    // GIVEN
+    // WHEN
+    let fx = Fixture::new();
+    let mut cmd = fx.cmd([
+        "--input-path",
+        "tests/data/input-8.txt",
+        "--ignore-first-n",
+        "1",
+        "--empty-components",
+        "drop",
+        "--absolute-root",
+    ]);
+
+    // THEN
+    assert_cmd_snapshot!(cmd, @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    /usr/l/bin
+    /home/u/p/lib.rs
+
+    ----- stderr -----
+    ");
+}
+
+#[test]
+fn replaces_empty_elements_with_placeholder() {
+    // GIVEN
+    // WHEN
+    let fx = Fixture::new();
+    let mut cmd = fx.cmd([
+        "--input-path",
+        "tests/data/input-8.txt",
+        "--empty-components",
+        "placeholder:_",
+    ]);
+
+    // THEN
+    assert_cmd_snapshot!(cmd, @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    _/u/_/l/b/_
+    _/h/u/p/lib.rs
+
+    ----- stderr -----
+    ");
+}
+
+#[test]
+fn keeps_elements_chosen_by_index() {
+    // GIVEN
+    // WHEN
+    let fx = Fixture::new();
+    let mut cmd = fx.cmd([
+        "--input-path",
+        "tests/data/input-3.txt",
+        "--ignore-last-n",
+        "0",
+        "--keep",
+        "1,-2:",
+    ]);
+
+    // THEN
+    assert_cmd_snapshot!(cmd, @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    s/main/s/a/billing/Server.scala
+    s/main/s/a/backup/Server.scala
+    s/main/s/a/backup/Components.scala
+    s/test/s/a/billing/ServerSpec.scala
+
+    ----- stderr -----
+    ");
+}
+
+#[test]
+fn uses_extraction_correctly() {
+    // GIVEN
+    // WHEN
+    let fx = Fixture::new();
+    let mut cmd = fx.cmd([
+        "--input-path",
+        "tests/data/input-5.txt",
+        "--extract-regex",
+        r"service-(\w)\w*-(v\d+)",
+        "--extract-template",
+        "$1$2",
+    ]);
+
+    // THEN
+    assert_cmd_snapshot!(cmd, @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    s/bv2/m/2024-03-17-add-invoices.sql
+    s/bv10/m/2023-11-02-init.sql
+
+    ----- stderr -----
+    ");
+}
+
+#[test]
+fn keeps_extensions_if_filename_aware() {
+    // GIVEN
+    // WHEN
+    let fx = Fixture::new();
+    let mut cmd = fx.cmd([
+        "--input-path",
+        "tests/data/input-1.txt",
+        "--ignore-last-n",
+        "0",
+        "--filename-aware",
+    ]);
+
+    // THEN
+    assert_cmd_snapshot!(cmd, @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    s/m/s/a/b/A.scala
+    s/m/s/a/b/C.scala
+    s/m/s/a/b/S.scala
+
+    ----- stderr -----
+    ");
+}
+
+#[test]
+fn respects_quoted_elements() {
+    // GIVEN
+    // WHEN
+    let fx = Fixture::new();
+    let mut cmd = fx.cmd([
+        "--input-path",
+        "tests/data/input-9.txt",
+        "--delimiter",
+        ",",
+        "--output-delimiter",
+        ",",
+        "--quote",
+        "\"",
+    ]);
+
+    // THEN
+    assert_cmd_snapshot!(cmd, @r#"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    S,E,"R&D, Berlin"
+    D,M,"Sales, ""EMEA"""
+
+    ----- stderr -----
+    "#);
+}
+
+#[test]
+fn quotes_elements_containing_the_output_delimiter() {
+    // GIVEN
+    // WHEN
+    let fx = Fixture::new();
+    let mut cmd = fx.cmd([
+        "--input-path",
+        "tests/data/input-9.txt",
+        "--delimiter",
+        ",",
+        "--output-delimiter",
+        ", ",
+        "--ignore-first-n",
+        "1",
+        "--quote",
+        "\"",
+    ]);
+
+    // THEN
+    assert_cmd_snapshot!(cmd, @r#"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    "Smith, John", E, "R&D, Berlin"
+    "Doe, Jane", M, "Sales, ""EMEA"""
+
+    ----- stderr -----
+    "#);
+}
+
+#[test]
+fn passes_bytes_that_are_not_utf8_through() {
+    // GIVEN
+    // WHEN
+    let fx = Fixture::new();
+    let mut cmd = fx.cmd(["--input-path", "tests/data/input-10.txt"]);
+    let output = cmd.output().expect("command should run");
+
+    // THEN
+    assert_eq!(output.stdout, b"h/c/p/lib.rs\n\xff/\xfe/archive.tar.gz\n");
+    assert_cmd_snapshot!(cmd, @r"
+    success: true
+    exit_code: 0
+    ----- stdout -----
+    h/c/p/lib.rs
+    �/�/archive.tar.gz
+
+    ----- stderr -----
+    ");
+}
+
+#[test]
+fn leaves_text_that_looks_like_escaped_bytes_as_is() {
+    // GIVEN
+    // WHEN
+    let fx = Fixture::new();
+    let mut cmd = fx.cmd(["--input-path", "tests/data/input-11.txt"]);
+    let output = cmd.output().expect("command should run");
+
+    // THEN
+    assert_eq!(output.stdout, "d/\u{10FF2F}/file\nc/l/file\n".as_bytes());
+}
+
+#[test]
+fn restores_escaped_bytes_only_for_tree_nodes_from_lines_that_had_them() {
+    // GIVEN
+    // WHEN
+    let fx = Fixture::new();
+    let mut cmd = fx.cmd([
+        "--input-path",
+        "tests/data/input-12.txt",
+        "--tree",
+        "--strategy",
+        "last",
+    ]);
+    let output = cmd.output().expect("command should run");
+
+    // THEN
+    let expected = [
+        "r\n└── \u{10FFFF}\n    └── file\n".as_bytes(),
+        b"\xe9\n",
+        "└── ".as_bytes(),
+        b"\xff\n",
+        "    └── file\n".as_bytes(),
+    ]
+    .concat();
+    assert_eq!(output.stdout, expected);
+}
+
+// FAILURES
+#[test]
+fn fails_if_no_source_is_provided() {
+    // GIVEN
+    // WHEN
+    let fx = Fixture::new();
+    let mut cmd = fx.base_cmd();
+
+    // THEN
+    assert_cmd_snapshot!(cmd, @r"
+    success: false
+    exit_code: 1
+    ----- stdout -----
+
+    ----- stderr -----
+    Error: a source needs to be provided (either a file or stdin)
+    ");
+}
+
+#[test]
+fn fails_if_more_than_one_source_is_provided() {
+    // GIVEN
+    // WHEN
+    let fx = Fixture::new();
+    let mut cmd = fx.cmd(["--input-path", "tests/data/input-1.txt", "--use-stdin"]);
+
+    // THEN
+    assert_cmd_snapshot!(cmd, @r"
+    success: false
+    exit_code: 1
+    ----- stdout -----
+
+    ----- stderr -----
+    Error: only one source (either a file or stdin) can be used at a time
+    ");
+}
+
+#[test]
+fn fails_if_input_file_is_non_existent() {
+    // GIVEN
+    // WHEN
+    let fx = Fixture::new();
+    let mut cmd = fx.cmd(["--input-path", "tests/data/nonexistent.txt"]);
+
+    // THEN
+    assert_cmd_snapshot!(cmd, @r"
+    success: false
+    exit_code: 1
+    ----- stdout -----
+
+    ----- stderr -----
+    Error: No such file or directory (os error 2)
+    ");
+}
+
+#[test]
+fn fails_if_input_file_is_empty() {
+    // GIVEN
+    // WHEN
+    let fx = Fixture::new();
+    let mut cmd = fx.cmd(["--input-path", "tests/data/empty.txt"]);
+
+    // THEN
+    assert_cmd_snapshot!(cmd, @r"
+    success: false
+    exit_code: 1
+    ----- stdout -----
+
+    ----- stderr -----
+    Error: nothing to shorten
+    ");
+}
+
+// the error's details depend on the engine
+#[cfg(feature = "regex")]
+#[test]
+fn fails_if_ignore_regex_is_incorrect() {
+    // GIVEN
+    // WHEN
+    let fx = Fixture::new();
+    let mut cmd = fx.cmd([
+        "--input-path",
+        "tests/data/input-1.txt",
+        "--ignore-regex",
+        "(?:scala|billing",
+    ]);
+
+    // THEN
+    assert_cmd_snapshot!(cmd, @r"
+    success: false
+    exit_code: 1
+    ----- stdout -----
+
+    ----- stderr -----
+    Error: couldn't compile ignore regex
+
+    Caused by:
+        regex parse error:
+            (?:scala|billing
+            ^
+        error: unclosed group
+    ");
+}
+
+#[cfg(not(any(
+    feature = "regex-automata",
+    feature = "regex-lite",
+    feature = "fancy-regex"
+)))]
+#[test]
+fn fails_if_regex_engine_is_not_built_in() {
+    // GIVEN
+    // WHEN
+    let fx = Fixture::new();
+    let mut cmd = fx.cmd([
+        "--input-path",
+        "tests/data/input-1.txt",
+        "--regex-engine",
+        "fancy-regex",
+    ]);
+
+    // THEN
+    assert_cmd_snapshot!(cmd, @r#"
+    success: false
+    exit_code: 2
+    ----- stdout -----
+
+    ----- stderr -----
+    error: invalid value 'fancy-regex' for '--regex-engine <ENGINE>': invalid regex engine "fancy-regex" (possible values: regex)
+
+    For more information, try '--help'.
+    "#);
+}
+
+#[test]
+fn fails_if_ignore_first_n_is_not_a_number() {
+    // GIVEN
+    // WHEN
+    let fx = Fixture::new();
+    let mut cmd = fx.cmd([
+        "--input-path",
+        "tests/data/input-1.txt",
+        "--ignore-first-n",
+        "blah",
+    ]);
+
+    // THEN
+    assert_cmd_snapshot!(cmd, @r"
+    success: false
+    exit_code: 2
+    ----- stdout -----
+
+    ----- stderr -----
+    error: invalid value 'blah' for '--ignore-first-n <NUMBER>': invalid digit found in string
+
+    For more information, try '--help'.
+    ");
+}
+
+#[test]
+fn fails_if_ignore_last_n_is_not_a_number() {
+    // GIVEN
+    // WHEN
+    let fx = Fixture::new();
+    let mut cmd = fx.cmd([
+        "--input-path",
+        "tests/data/input-1.txt",
+        "--ignore-last-n",
+        "blah",
+    ]);
+
+    // THEN
+    assert_cmd_snapshot!(cmd, @r"
+    success: false
+    exit_code: 2
+    ----- stdout -----
+
+    ----- stderr -----
+    error: invalid value 'blah' for '--ignore-last-n <NUMBER>': invalid digit found in string
+
+    For more information, try '--help'.
+    ");
+}
+
+#[test]
+fn fails_if_strategy_is_invalid() {
+    // GIVEN
+    // WHEN
+    let fx = Fixture::new();
+    let mut cmd = fx.cmd([
+        "--input-path",
+        "tests/data/input-1.txt",
+        "--strategy",
+        "middle",
+    ]);
+
+    // THEN
+    assert_cmd_snapshot!(cmd, @r#"
+    success: false
+    exit_code: 2
+    ----- stdout -----
+
+    ----- stderr -----
+    error: invalid value 'middle' for '--strategy <STRATEGY>': invalid strategy "middle" (possible values: first, first:N, last, last:N, first-last)
+
+    For more information, try '--help'.
+    "#);
+}
+
+#[test]
+fn fails_if_max_width_is_invalid() {
+    // GIVEN
+    // WHEN
+    let fx = Fixture::new();
+    let mut cmd = fx.cmd([
+        "--input-path",
+        "tests/data/input-1.txt",
+        "--max-width",
+        "wide",
+    ]);
+
+    // THEN
+    assert_cmd_snapshot!(cmd, @r#"
+    success: false
+    exit_code: 2
+    ----- stdout -----
+
+    ----- stderr -----
+    error: invalid value 'wide' for '--max-width <COLUMNS|auto>': expected a number or "auto"
+
+    For more information, try '--help'.
+    "#);
+}
+
+#[test]
+fn fails_if_elide_is_invalid() {
+    // GIVEN
+    // WHEN
+    let fx = Fixture::new();
+    let mut cmd = fx.cmd(["--input-path", "tests/data/input-1.txt", "--elide", "2"]);
+
+    // THEN
+    assert_cmd_snapshot!(cmd, @r#"
+    success: false
+    exit_code: 2
+    ----- stdout -----
+
+    ----- stderr -----
+    error: invalid value '2' for '--elide <FIRST:LAST>': expected "FIRST:LAST" (eg, "1:2")
+
+    For more information, try '--help'.
+    "#);
+}
+
+#[test]
+fn fails_if_empty_components_policy_is_invalid() {
+    // GIVEN
+    // WHEN
+    let fx = Fixture::new();
+    let mut cmd = fx.cmd([
+        "--input-path",
+        "tests/data/input-8.txt",
+        "--empty-components",
+        "skip",
+    ]);
+
+    // THEN
+    assert_cmd_snapshot!(cmd, @r#"
+    success: false
+    exit_code: 2
+    ----- stdout -----
+
+    ----- stderr -----
+    error: invalid value 'skip' for '--empty-components <POLICY>': invalid empty component policy "skip" (possible values: keep, drop, placeholder:TEXT)
+
+    For more information, try '--help'.
+    "#);
+}
+
+#[test]
+fn fails_if_keep_selector_is_invalid() {
+    // GIVEN
+    // WHEN
+    let fx = Fixture::new();
+    let mut cmd = fx.cmd(["--input-path", "tests/data/input-1.txt", "--keep", "1::0"]);
+
+    // THEN
+    assert_cmd_snapshot!(cmd, @r#"
+    success: false
+    exit_code: 2
+    ----- stdout -----
+
+    ----- stderr -----
+    error: invalid value '1::0' for '--keep <INDICES>': step cannot be zero in slice "1::0"
+
+    For more information, try '--help'.
+    "#);
+}
+
+// the error's details depend on the engine
+#[cfg(feature = "regex")]
+#[test]
+fn fails_if_delimiter_regex_is_incorrect() {
+    // GIVEN
+    // WHEN
+    let fx = Fixture::new();
+    let mut cmd = fx.cmd([
+        "--input-path",
+        "tests/data/input-4.txt",
+        "--delimiter-regex",
+        "[/.",
+    ]);
+
+    // THEN
+    assert_cmd_snapshot!(cmd, @r"
+    success: false
+    exit_code: 1
+    ----- stdout -----
+
+    ----- stderr -----
+    Error: couldn't compile delimiter regex
+
+    Caused by:
+        regex parse error:
+            [/.
+            ^
+        error: unclosed character class
+    ");
+}
+
+#[test]
+fn fails_if_keep_delimiters_is_used_with_output_delimiter() {
+    // GIVEN
+    // WHEN
+    let fx = Fixture::new();
+    let mut cmd = fx.cmd([
+        "--input-path",
+        "tests/data/input-4.txt",
+        "--keep-delimiters",
+        "--output-delimiter",
+        "|",
+    ]);
+
+    // THEN
+    assert_cmd_snapshot!(cmd, @r"
+    success: false
+    exit_code: 2
+    ----- stdout -----
+
+    ----- stderr -----
+    error: the argument '--keep-delimiters' cannot be used with '--output-delimiter <STRING>'
+
+    Usage: sqdj --input-path <STRING> --keep-delimiters
+
+    For more information, try '--help'.
+    ");
+}
+
+#[test]
+fn fails_if_delimiter_is_empty() {
+    // GIVEN
+    // WHEN
+    let fx = Fixture::new();
+    let mut cmd = fx.cmd(["--input-path", "tests/data/input-1.txt", "--delimiter", ""]);
+
+    // THEN
+    assert_cmd_snapshot!(cmd, @r"
+    success: false
+    exit_code: 1
+    ----- stdout -----
+
+    ----- stderr -----
+    Error: delimiter cannot be empty
+    ");
+}
+
+#[test]
+fn fails_if_strategy_abbreviates_to_nothing() {
+    // GIVEN
+    // WHEN
+    let fx = Fixture::new();
+    let mut cmd = fx.cmd([
+        "--input-path",
+        "tests/data/input-1.txt",
+        "--strategy",
+        "first:0",
+    ]);
+
+    // THEN
+    assert_cmd_snapshot!(cmd, @r#"
+    success: false
+    exit_code: 1
+    ----- stdout -----
+
+    ----- stderr -----
+    Error: strategy "first:0" would abbreviate components to nothing
+    "#);
+}
+
+#[test]
+fn fails_if_aliases_file_is_invalid() {
+    // GIVEN
+    // WHEN
+    let fx = Fixture::new();
+    let mut cmd = fx.cmd([
+        "--input-path",
+        "tests/data/input-3.txt",
+        "--aliases-path",
+        "tests/data/aliases-2.txt",
+    ]);
+
+    // THEN
+    assert_cmd_snapshot!(cmd, @r#"
+    success: false
+    exit_code: 1
+    ----- stdout -----
+
+    ----- stderr -----
+    Error: couldn't parse alias on line 2
+
+    Caused by:
+        expected "<sequence> => <alias>"
+    "#);
+}
+
+#[test]
+fn fails_if_dot_is_used_without_tree() {
+    // GIVEN
+    // WHEN
+    let fx = Fixture::new();
+    let mut cmd = fx.cmd(["--input-path", "tests/data/input-3.txt", "--dot"]);
+
+    // THEN
+    assert_cmd_snapshot!(cmd, @r"
+    success: false
+    exit_code: 2
+    ----- stdout -----
+
+    ----- stderr -----
+    error: the following required arguments were not provided:
+      --tree
+
+    Usage: sqdj --tree --input-path <STRING> --dot
+
+    For more information, try '--help'.
+    ");
+}
+
+#[test]
+fn fails_if_rules_file_is_invalid() {
+    // GIVEN
+    // WHEN
+    let fx = Fixture::new();
+    let mut cmd = fx.cmd([
+        "--input-path",
+        "tests/data/input-3.txt",
+        "--rules-path",
+        "tests/data/rules-2.txt",
+    ]);
+
+    // THEN
+    assert_cmd_snapshot!(cmd, @r#"
+    success: false
+    exit_code: 1
+    ----- stdout -----
+
+    ----- stderr -----
+    Error: couldn't parse rule on line 2
+
+    Caused by:
+        invalid action "rename:sc" (possible values: keep, shorten, alias:TEXT, drop, strategy:STRATEGY)
+    "#);
+}
+
+#[test]
+fn fails_if_extract_regex_is_provided_without_template() {
+    // GIVEN
+    // WHEN
+    let fx = Fixture::new();
+    let mut cmd = fx.cmd([
+        "--input-path",
+        "tests/data/input-5.txt",
+        "--extract-regex",
+        r"service-(\w)\w*-(v\d+)",
+    ]);
+
+    // THEN
+    assert_cmd_snapshot!(cmd, @r"
+    success: false
+    exit_code: 2
+    ----- stdout -----
+
+    ----- stderr -----
+    error: the following required arguments were not provided:
+      --extract-template <STRING>
+
+    Usage: sqdj --extract-regex <STRING> --extract-template <STRING> --input-path <STRING>
 
     For more information, try '--help'.
     ");
@@ -0,0 +1,323 @@
+use crate::units;
+use alloc::borrow::Cow;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::sync::Arc;
+use core::fmt;
+use core::str::FromStr;
+
+const ELLIPSIS: &str = "…";
+
+/// Determines how a single component is abbreviated.
+///
+/// Implement this to plug your own logic into [`Config`](crate::Config) via
+/// [`Strategy::custom`].
+///
+/// Example:
+/// ```
+/// use squidge::{Abbreviator, Config, Strategy, shorten_line};
+/// use std::borrow::Cow;
+///
+/// #[derive(Debug)]
+/// struct Uppercase;
+///
+/// impl Abbreviator for Uppercase {
+///     fn abbreviate<'c>(&self, component: &'c str) -> Cow<'c, str> {
+///         component.chars().take(1).flat_map(char::to_uppercase).collect::<String>().into()
+///     }
+/// }
+///
+/// let cfg = Config {
+///     strategy: Strategy::custom(Uppercase),
+///     ..Config::default()
+/// };
+/// assert_eq!(shorten_line(&cfg, "module/service/lib.rs"), vec!["M", "S", "lib.rs"]);
+/// ```
+pub trait Abbreviator: fmt::Debug + Send + Sync {
+    /// Returns the abbreviated form of `component`.
+    fn abbreviate<'c>(&self, component: &'c str) -> Cow<'c, str>;
+}
+
+/// The abbreviation strategy used by [`Config`](crate::Config).
+///
+/// "Characters" here are extended grapheme clusters when the `graphemes` feature is enabled (the
+/// default), and `char`s otherwise.
+///
+/// Apart from the built-in strategies, any [`Abbreviator`] can be used via [`Strategy::custom`].
+///
+/// Built-in strategies can also be parsed from strings:
+/// ```
+/// use squidge::Strategy;
+///
+/// assert_eq!("first".parse::<Strategy>().unwrap(), Strategy::FirstChars(1));
+/// assert_eq!("first:3".parse::<Strategy>().unwrap(), Strategy::FirstChars(3));
+/// assert_eq!("last:2".parse::<Strategy>().unwrap(), Strategy::LastChars(2));
+/// assert_eq!("first-last".parse::<Strategy>().unwrap(), Strategy::FirstAndLast);
+/// ```
+#[derive(Debug, Clone)]
+pub enum Strategy {
+    /// Keep the first n characters (`service` -> `se` for n = 2)
+    FirstChars(usize),
+    /// Keep the last n characters (`service` -> `ce` for n = 2)
+    LastChars(usize),
+    /// Keep the first and the last characters, separated by an ellipsis (`service` -> `s…e`)
+    FirstAndLast,
+    /// A user provided abbreviator
+    Custom(Arc<dyn Abbreviator>),
+}
+
+impl Strategy {
+    /// Wraps a user provided [`Abbreviator`].
+    pub fn custom<A: Abbreviator + 'static>(abbreviator: A) -> Self {
+        Strategy::Custom(Arc::new(abbreviator))
+    }
+}
+
+impl Default for Strategy {
+    fn default() -> Self {
+        Strategy::FirstChars(1)
+    }
+}
+
+impl PartialEq for Strategy {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Strategy::FirstChars(a), Strategy::FirstChars(b)) => a == b,
+            (Strategy::LastChars(a), Strategy::LastChars(b)) => a == b,
+            (Strategy::FirstAndLast, Strategy::FirstAndLast) => true,
+            (Strategy::Custom(a), Strategy::Custom(b)) => Arc::ptr_eq(a, b),
+            _ => false,
+        }
+    }
+}
+
+impl Abbreviator for Strategy {
+    fn abbreviate<'c>(&self, component: &'c str) -> Cow<'c, str> {
+        match self {
+            Strategy::FirstChars(n) => Cow::Borrowed(units::first_units(component, *n)),
+            Strategy::LastChars(n) => Cow::Borrowed(units::last_units(component, *n)),
+            Strategy::FirstAndLast => {
+                let mut starts = units::unit_starts(component);
+                match (starts.nth(1), starts.next_back()) {
+                    // abbreviating wouldn't make components this short any shorter
+                    (Some(second), Some(last)) if starts.next().is_some() => Cow::Owned(format!(
+                        "{}{ELLIPSIS}{}",
+                        &component[..second],
+                        &component[last..]
+                    )),
+                    _ => Cow::Borrowed(component),
+                }
+            }
+            Strategy::Custom(abbreviator) => abbreviator.abbreviate(component),
+        }
+    }
+}
+
+impl fmt::Display for Strategy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Strategy::FirstChars(1) => write!(f, "first"),
+            Strategy::FirstChars(n) => write!(f, "first:{n}"),
+            Strategy::LastChars(1) => write!(f, "last"),
+            Strategy::LastChars(n) => write!(f, "last:{n}"),
+            Strategy::FirstAndLast => write!(f, "first-last"),
+            Strategy::Custom(_) => write!(f, "custom"),
+        }
+    }
+}
+
+/// Error returned when parsing a [`Strategy`] from a string fails.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseStrategyError(String);
+
+impl fmt::Display for ParseStrategyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            r#"invalid strategy "{}" (possible values: first, first:N, last, last:N, first-last)"#,
+            self.0
+        )
+    }
+}
+
+impl core::error::Error for ParseStrategyError {}
+
+impl FromStr for Strategy {
+    type Err = ParseStrategyError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let err = || ParseStrategyError(s.to_string());
+        let (name, count) = match s.split_once(':') {
+            Some((name, count)) => (name, Some(count.parse::<usize>().map_err(|_| err())?)),
+            None => (s, None),
+        };
+
+        match (name, count) {
+            ("first", count) => Ok(Strategy::FirstChars(count.unwrap_or(1))),
+            ("last", count) => Ok(Strategy::LastChars(count.unwrap_or(1))),
+            ("first-last", None) => Ok(Strategy::FirstAndLast),
+            _ => Err(err()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec::Vec;
+    use insta::assert_yaml_snapshot;
+
+    #[test]
+    fn first_chars_works() {
+        // GIVEN
+        let components = ["service", "ab", "", "ñandú"];
+
+        // WHEN
+        let result: Vec<_> = components
+            .iter()
+            .map(|c| Strategy::FirstChars(3).abbreviate(c))
+            .collect();
+
+        // THEN
+        assert_yaml_snapshot!(result, @r#"
+        - ser
+        - ab
+        - ""
+        - ñan
+        "#);
+    }
+
+    #[test]
+    fn last_chars_works() {
+        // GIVEN
+        let components = ["service", "ab", "", "ñandú"];
+
+        // WHEN
+        let result: Vec<_> = components
+            .iter()
+            .map(|c| Strategy::LastChars(3).abbreviate(c))
+            .collect();
+
+        // THEN
+        assert_yaml_snapshot!(result, @r#"
+        - ice
+        - ab
+        - ""
+        - ndú
+        "#);
+    }
+
+    #[test]
+    fn zero_chars_results_in_empty_components() {
+        // GIVEN
+        let component = "service";
+
+        // WHEN
+        let first = Strategy::FirstChars(0).abbreviate(component);
+        let last = Strategy::LastChars(0).abbreviate(component);
+
+        // THEN
+        assert_eq!(first, "");
+        assert_eq!(last, "");
+    }
+
+    #[test]
+    fn first_and_last_works() {
+        // GIVEN
+        let components = ["service", "abc", "ab", "a", "", "ñandú"];
+
+        // WHEN
+        let result: Vec<_> = components
+            .iter()
+            .map(|c| Strategy::FirstAndLast.abbreviate(c))
+            .collect();
+
+        // THEN
+        assert_yaml_snapshot!(result, @r#"
+        - s…e
+        - abc
+        - ab
+        - a
+        - ""
+        - ñ…ú
+        "#);
+    }
+
+    #[cfg(feature = "graphemes")]
+    #[test]
+    fn strategies_work_on_grapheme_clusters() {
+        // GIVEN
+        let components = [
+            // Devanagari, with a conjunct (क्ष) and dependent vowel signs
+            "क्षेत्रीय",
+            // Hangul, written as conjoining jamo (한국어)
+            "\u{1112}\u{1161}\u{11ab}\u{1100}\u{116e}\u{11a8}\u{110b}\u{1165}",
+            // emoji ZWJ sequence, skin tone modifier, and a flag
+            "👨‍👩‍👧👍🏽🇯🇵",
+            // e + combining acute accent
+            "e\u{301}cole",
+        ];
+        let strategies = [
+            Strategy::FirstChars(1),
+            Strategy::LastChars(1),
+            Strategy::FirstAndLast,
+        ];
+
+        // WHEN
+        let result: Vec<Vec<_>> = strategies
+            .iter()
+            .map(|s| components.iter().map(|c| s.abbreviate(c)).collect())
+            .collect();
+
+        // THEN
+        assert_yaml_snapshot!(result, @r"
+        - - क्षे
+          - 한
+          - 👨‍👩‍👧
+          - é
+        - - य
+          - 어
+          - 🇯🇵
+          - e
+        - - क्षेत्रीय
+          - 한국어
+          - 👨‍👩‍👧👍🏽🇯🇵
+          - é…e
+        ");
+    }
+
+    #[test]
+    fn parsing_strategies_round_trips() {
+        // GIVEN
+        let inputs = ["first", "first:3", "last", "last:2", "first-last"];
+
+        // WHEN
+        let result: Vec<_> = inputs
+            .iter()
+            .map(|s| s.parse::<Strategy>().unwrap().to_string())
+            .collect();
+
+        // THEN
+        assert_eq!(result, inputs);
+    }
+
+    #[test]
+    fn parsing_invalid_strategies_fails() {
+        // GIVEN
+        let inputs = ["middle", "first:x", "first-last:2", ""];
+
+        // WHEN
+        let result: Vec<_> = inputs
+            .iter()
+            .map(|s| s.parse::<Strategy>().unwrap_err().to_string())
+            .collect();
+
+        // THEN
+        assert_yaml_snapshot!(result, @r#"
+        - "invalid strategy \"middle\" (possible values: first, first:N, last, last:N, first-last)"
+        - "invalid strategy \"first:x\" (possible values: first, first:N, last, last:N, first-last)"
+        - "invalid strategy \"first-last:2\" (possible values: first, first:N, last, last:N, first-last)"
+        - "invalid strategy \"\" (possible values: first, first:N, last, last:N, first-last)"
+        "#);
+    }
+}
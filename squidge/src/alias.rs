@@ -0,0 +1,273 @@
+use crate::delimiter::Split;
+use alloc::borrow::Cow;
+use alloc::collections::VecDeque;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+/// Replaces a sequence of components with a single token.
+///
+/// Aliases listed in [`Config::aliases`](crate::Config::aliases) are applied before anything else;
+/// where several of them match at the same position, the longest one is used. The token counts as
+/// a single component (eg, for `ignore_first_n`), and is never shortened.
+///
+/// Example:
+/// ```
+/// use squidge::{Alias, Config, shorten_line};
+///
+/// let cfg = Config {
+///     aliases: vec![
+///         Alias::new(["src", "main", "scala"], "main"),
+///         Alias::new(["src", "test", "scala"], "test"),
+///     ],
+///     ..Config::default()
+/// };
+/// let result = shorten_line(&cfg, "src/main/scala/admin/billing/Server.scala");
+/// assert_eq!(result, vec!["main", "a", "b", "Server.scala"]);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Alias {
+    /// Components to replace, in order
+    pub sequence: Vec<String>,
+    /// Token to replace them with
+    pub token: String,
+}
+
+impl Alias {
+    /// Creates an alias replacing `sequence` with `token`.
+    pub fn new<S: Into<String>>(
+        sequence: impl IntoIterator<Item = S>,
+        token: impl Into<String>,
+    ) -> Self {
+        Alias {
+            sequence: sequence.into_iter().map(Into::into).collect(),
+            token: token.into(),
+        }
+    }
+}
+
+/// A component of a line, or an alias token that replaced several of them.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub(crate) enum Part<'a, 'l> {
+    Component(Cow<'l, str>),
+    Alias(&'a str),
+}
+
+/// A component, along with the delimiter that follows it, and the text of the line it comes from.
+type Component<'l> = (Cow<'l, str>, Option<&'l str>, &'l str);
+
+/// Iterator over the parts of a line after applying aliases, along with the delimiters that follow
+/// them.
+#[derive(Debug, Clone)]
+pub(crate) struct Aliased<'a, 'l> {
+    aliases: &'a [Alias],
+    split: Split<'a, 'l>,
+    // components read ahead of the current one while matching aliases
+    upcoming: VecDeque<Component<'l>>,
+    leading: Option<(&'a str, Option<&'l str>, &'l str)>,
+    root: Option<&'l str>,
+}
+
+impl<'a, 'l> Aliased<'a, 'l> {
+    pub(crate) fn new(aliases: &'a [Alias], split: Split<'a, 'l>) -> Self {
+        Aliased {
+            aliases,
+            split,
+            upcoming: VecDeque::new(),
+            leading: None,
+            root: None,
+        }
+    }
+
+    /// Records the leading delimiter of an absolute line, which isn't yielded as a part.
+    pub(crate) fn with_root(mut self, root: Option<&'l str>) -> Self {
+        self.root = root;
+        self
+    }
+
+    /// The leading delimiter of the line, if it's absolute (and its root is preserved).
+    pub(crate) fn root(&self) -> Option<&'l str> {
+        self.root
+    }
+
+    /// Yields `token` (followed by `delimiter`) in place of `source` before the parts of the
+    /// split, as is done for substituted prefixes.
+    pub(crate) fn after(
+        mut self,
+        token: &'a str,
+        delimiter: Option<&'l str>,
+        source: &'l str,
+    ) -> Self {
+        self.leading = Some((token, delimiter, source));
+        self
+    }
+
+    /// Whether the first part is a substituted prefix.
+    pub(crate) fn substitutes_prefix(&self) -> bool {
+        self.leading.is_some()
+    }
+
+    /// Returns the next part, along with the delimiter that follows it, and the text of the line
+    /// it stands for.
+    pub(crate) fn next_with_source(&mut self) -> Option<(Part<'a, 'l>, Option<&'l str>, &'l str)> {
+        if let Some((token, delimiter, source)) = self.leading.take() {
+            return Some((Part::Alias(token), delimiter, source));
+        }
+
+        if let Some(alias) = self.longest_match() {
+            let first = self.upcoming[0].2;
+            let (_, delimiter, last) = self.upcoming.drain(..alias.sequence.len()).next_back()?;
+            return Some((
+                Part::Alias(&alias.token),
+                delimiter,
+                self.split.between(first, last),
+            ));
+        }
+
+        self.upcoming
+            .pop_front()
+            .or_else(|| self.split.next_with_source())
+            .map(|(component, delimiter, source)| (Part::Component(component), delimiter, source))
+    }
+
+    /// Returns the longest alias matching the upcoming components, which are read ahead (only as
+    /// far as an alias starting with the next component needs) into `upcoming`.
+    fn longest_match(&mut self) -> Option<&'a Alias> {
+        let mut longest: Option<&'a Alias> = None;
+        for alias in self.aliases {
+            let Some(first) = alias.sequence.first() else {
+                continue;
+            };
+            if longest.is_some_and(|longest| longest.sequence.len() >= alias.sequence.len())
+                || !self.read_ahead(1)
+                || self.upcoming[0].0 != *first
+            {
+                continue;
+            }
+
+            let len = alias.sequence.len();
+            if self.read_ahead(len)
+                && self
+                    .upcoming
+                    .iter()
+                    .zip(&alias.sequence)
+                    .all(|((component, _, _), expected)| component == expected)
+            {
+                longest = Some(alias);
+            }
+        }
+
+        longest
+    }
+
+    /// Reads components ahead until `n` of them are upcoming; `false` if the line has fewer left.
+    fn read_ahead(&mut self, n: usize) -> bool {
+        while self.upcoming.len() < n {
+            match self.split.next_with_source() {
+                Some(component) => self.upcoming.push_back(component),
+                None => return false,
+            }
+        }
+
+        true
+    }
+}
+
+impl<'a, 'l> Iterator for Aliased<'a, 'l> {
+    type Item = (Part<'a, 'l>, Option<&'l str>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_with_source()
+            .map(|(part, delimiter, _)| (part, delimiter))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Delimiter;
+    use alloc::vec;
+    use insta::assert_yaml_snapshot;
+
+    #[test]
+    fn aliased_prefers_the_longest_match() {
+        // GIVEN
+        let aliases = [
+            Alias::new(["src", "main"], "sm"),
+            Alias::new(["src", "main", "scala"], "main"),
+            Alias::new(["src", "main", "java"], "java"),
+            Alias::new(["Server.scala"], "server"),
+        ];
+        let delimiter = Delimiter::from("/");
+
+        // WHEN
+        let result: Vec<_> = Aliased::new(
+            &aliases,
+            delimiter.split("src/main/scala/src/main/Server.scala"),
+        )
+        .map(|(part, delimiter)| match part {
+            Part::Component(component) => (component, false, delimiter),
+            Part::Alias(token) => (Cow::Borrowed(token), true, delimiter),
+        })
+        .collect();
+
+        // THEN
+        assert_yaml_snapshot!(result, @r"
+        - - main
+          - true
+          - /
+        - - sm
+          - true
+          - /
+        - - server
+          - true
+          - ~
+        ");
+    }
+
+    #[test]
+    fn aliased_does_not_match_partial_sequences() {
+        // GIVEN
+        let aliases = [Alias::new(["src", "main", "scala"], "main")];
+        let delimiter = Delimiter::from("/");
+
+        // WHEN
+        let result: Vec<_> = Aliased::new(&aliases, delimiter.split("src/main"))
+            .map(|(part, _)| part)
+            .collect();
+
+        // THEN
+        assert_eq!(
+            result,
+            vec![
+                Part::Component(Cow::Borrowed("src")),
+                Part::Component(Cow::Borrowed("main"))
+            ]
+        );
+    }
+
+    #[test]
+    fn aliased_yields_the_components_read_ahead_for_partial_matches() {
+        // GIVEN
+        let aliases = [
+            Alias::new(["src", "main", "scala"], "main"),
+            Alias::new(["main", "java"], "java"),
+        ];
+        let delimiter = Delimiter::from("/");
+
+        // WHEN
+        let result: Vec<_> =
+            Aliased::new(&aliases, delimiter.split("src/main/java/src/main")).collect();
+
+        // THEN
+        assert_eq!(
+            result,
+            vec![
+                (Part::Component(Cow::Borrowed("src")), Some("/")),
+                (Part::Alias("java"), Some("/")),
+                (Part::Component(Cow::Borrowed("src")), Some("/")),
+                (Part::Component(Cow::Borrowed("main")), None),
+            ]
+        );
+    }
+}
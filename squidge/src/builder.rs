@@ -0,0 +1,485 @@
+use crate::{
+    Action, Alias, Collapse, Config, Delimiter, Elision, EmptyComponents, Engine, Error,
+    Extraction, Prefix, Quoting, Regex, Rule, Selector, Strategy,
+};
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+/// Builds a [`Config`], validating its values along the way.
+///
+/// Created via [`Config::builder`]; every setting that isn't provided falls back to the value in
+/// [`Config::default`].
+///
+/// Example:
+/// ```
+/// use squidge::{Config, Error, Strategy, shorten_line};
+///
+/// let cfg = Config::builder()
+///     .delimiters(vec!["/", "::"])
+///     .ignore_first_n(1)
+///     .ignore_regex("billing|utils")
+///     .strategy(Strategy::FirstChars(2))
+///     .build()
+///     .unwrap();
+/// let result = shorten_line(&cfg, "src/main::scala/billing/Server.scala");
+/// assert_eq!(result, vec!["src", "ma", "sc", "billing", "Server.scala"]);
+///
+/// let err = Config::builder().delimiter("").build().unwrap_err();
+/// assert!(matches!(err, Error::EmptyDelimiter));
+/// ```
+#[derive(Debug, Default)]
+#[must_use]
+pub struct ConfigBuilder {
+    delimiter: Option<Delimiter>,
+    delimiter_regex: Option<String>,
+    output_delimiter: Option<String>,
+    keep_delimiters: Option<bool>,
+    quoting: Option<Quoting>,
+    ignore_first_n: Option<usize>,
+    ignore_last_n: Option<usize>,
+    ignore_regex: Option<String>,
+    ignore_indices: Option<Selector>,
+    empty_components: Option<EmptyComponents>,
+    absolute_root: Option<bool>,
+    rules: Vec<Rule>,
+    prefixes: Vec<Prefix>,
+    aliases: Vec<(String, String)>,
+    extraction: Option<(String, String)>,
+    strategy: Option<Strategy>,
+    filename_aware: Option<bool>,
+    collapse: Option<Collapse>,
+    elision: Option<Elision>,
+    max_width: Option<usize>,
+    engine: Option<Engine>,
+}
+
+impl ConfigBuilder {
+    /// Delimiter to split the line on.
+    pub fn delimiter(mut self, delimiter: impl Into<Delimiter>) -> Self {
+        self.delimiter = Some(delimiter.into());
+        self.delimiter_regex = None;
+        self
+    }
+
+    /// Several delimiters to split the line on.
+    pub fn delimiters<S: Into<String>>(self, delimiters: impl IntoIterator<Item = S>) -> Self {
+        self.delimiter(Delimiter::Set(
+            delimiters.into_iter().map(Into::into).collect(),
+        ))
+    }
+
+    /// Regex to split the line on; compiled when the config is built.
+    pub fn delimiter_regex(mut self, pattern: impl Into<String>) -> Self {
+        self.delimiter_regex = Some(pattern.into());
+        self.delimiter = None;
+        self
+    }
+
+    /// Delimiter to join the shortened components with.
+    pub fn output_delimiter(mut self, output_delimiter: impl Into<String>) -> Self {
+        self.output_delimiter = Some(output_delimiter.into());
+        self
+    }
+
+    /// Whether to join the shortened components with their original delimiters.
+    pub fn keep_delimiters(mut self, keep_delimiters: bool) -> Self {
+        self.keep_delimiters = Some(keep_delimiters);
+        self
+    }
+
+    /// Quoting mode, in which delimiters within quoted components don't split them.
+    pub fn quoting(mut self, quoting: Quoting) -> Self {
+        self.quoting = Some(quoting);
+        self
+    }
+
+    /// Number of elements to ignore (for shortening) from the start.
+    pub fn ignore_first_n(mut self, n: usize) -> Self {
+        self.ignore_first_n = Some(n);
+        self
+    }
+
+    /// Number of elements to ignore (for shortening) from the end.
+    pub fn ignore_last_n(mut self, n: usize) -> Self {
+        self.ignore_last_n = Some(n);
+        self
+    }
+
+    /// Regex to determine which components to ignore while shortening; compiled when the config
+    /// is built.
+    pub fn ignore_regex(mut self, pattern: impl Into<String>) -> Self {
+        self.ignore_regex = Some(pattern.into());
+        self
+    }
+
+    /// Selector for the indices of components to ignore while shortening.
+    pub fn ignore_indices(mut self, selector: Selector) -> Self {
+        self.ignore_indices = Some(selector);
+        self
+    }
+
+    /// What happens to empty components.
+    pub fn empty_components(mut self, empty_components: EmptyComponents) -> Self {
+        self.empty_components = Some(empty_components);
+        self
+    }
+
+    /// Whether to preserve the leading delimiter of absolute lines, without it counting as a
+    /// component.
+    pub fn absolute_root(mut self, absolute_root: bool) -> Self {
+        self.absolute_root = Some(absolute_root);
+        self
+    }
+
+    /// Adds a rule; rules are consulted in the order they're added.
+    pub fn rule(mut self, rule: Rule) -> Self {
+        self.rules.push(rule);
+        self
+    }
+
+    /// Adds several rules; rules are consulted in the order they're added.
+    pub fn rules(mut self, rules: impl IntoIterator<Item = Rule>) -> Self {
+        self.rules.extend(rules);
+        self
+    }
+
+    /// Adds a prefix that replaces `path` at the start of lines (eg, "/home/alice") with `token`.
+    pub fn prefix(mut self, path: impl Into<String>, token: impl Into<String>) -> Self {
+        self.prefixes.push(Prefix::new(path, token));
+        self
+    }
+
+    /// Adds an alias that replaces `sequence` (split on the delimiter when the config is built,
+    /// eg, "src/main/scala") with `token`.
+    pub fn alias(mut self, sequence: impl Into<String>, token: impl Into<String>) -> Self {
+        self.aliases.push((sequence.into(), token.into()));
+        self
+    }
+
+    /// Regex with capture groups, and the template that matching components are replaced with;
+    /// the regex is compiled when the config is built.
+    pub fn extraction(
+        mut self,
+        pattern: impl Into<String>,
+        replacement: impl Into<String>,
+    ) -> Self {
+        self.extraction = Some((pattern.into(), replacement.into()));
+        self
+    }
+
+    /// Strategy used to abbreviate components.
+    pub fn strategy(mut self, strategy: Strategy) -> Self {
+        self.strategy = Some(strategy);
+        self
+    }
+
+    /// Whether to keep extensions and leading dots of components as is while abbreviating them.
+    pub fn filename_aware(mut self, filename_aware: bool) -> Self {
+        self.filename_aware = Some(filename_aware);
+        self
+    }
+
+    /// Whether to collapse repeated components (or blocks of them) into a single annotated token.
+    pub fn collapse(mut self, collapse: Collapse) -> Self {
+        self.collapse = Some(collapse);
+        self
+    }
+
+    /// How many components to keep from the start and end of lines, and what to replace the
+    /// ones in between with.
+    pub fn elision(mut self, elision: Elision) -> Self {
+        self.elision = Some(elision);
+        self
+    }
+
+    /// Width budget (in terminal columns) for the shortened line.
+    pub fn max_width(mut self, max_width: usize) -> Self {
+        self.max_width = Some(max_width);
+        self
+    }
+
+    /// Engine to compile the delimiter, ignore, and extraction regexes with; defaults to
+    /// [`Engine::default`]. Regexes in rules are compiled when the rules are parsed, with the
+    /// engine they name (eg, `fancy-regex:PATTERN`; see [`Rule`]).
+    pub fn engine(mut self, engine: Engine) -> Self {
+        self.engine = Some(engine);
+        self
+    }
+
+    /// Validates the provided values and builds the config.
+    pub fn build(self) -> Result<Config, Error> {
+        let default = Config::default();
+        let engine = self.engine.unwrap_or_default();
+        let compile = |pattern: &str| Regex::with_engine(pattern, engine);
+
+        let delimiter = match (self.delimiter, self.delimiter_regex) {
+            (_, Some(pattern)) => {
+                Delimiter::Regex(compile(&pattern).map_err(Error::InvalidDelimiterRegex)?)
+            }
+            (Some(delimiter), None) => delimiter,
+            (None, None) => default.delimiter,
+        };
+
+        let aliases = self
+            .aliases
+            .into_iter()
+            .map(|(sequence, token)| {
+                Alias::new(
+                    delimiter.split(&sequence).map(|(component, _)| component),
+                    token,
+                )
+            })
+            .collect();
+
+        let ignore_regex = self
+            .ignore_regex
+            .map(|pattern| compile(&pattern).map_err(Error::InvalidIgnoreRegex))
+            .transpose()?;
+
+        let extraction = self
+            .extraction
+            .map(|(pattern, replacement)| {
+                compile(&pattern)
+                    .map(|regex| Extraction { regex, replacement })
+                    .map_err(Error::InvalidExtractionRegex)
+            })
+            .transpose()?;
+
+        let cfg = Config {
+            delimiter,
+            output_delimiter: self.output_delimiter.unwrap_or(default.output_delimiter),
+            keep_delimiters: self.keep_delimiters.unwrap_or(default.keep_delimiters),
+            quoting: self.quoting,
+            ignore_first_n: self.ignore_first_n.unwrap_or(default.ignore_first_n),
+            ignore_last_n: self.ignore_last_n.unwrap_or(default.ignore_last_n),
+            ignore_regex,
+            ignore_indices: self.ignore_indices,
+            empty_components: self.empty_components.unwrap_or(default.empty_components),
+            absolute_root: self.absolute_root.unwrap_or(default.absolute_root),
+            rules: self.rules,
+            prefixes: self.prefixes,
+            aliases,
+            extraction,
+            strategy: self.strategy.unwrap_or(default.strategy),
+            filename_aware: self.filename_aware.unwrap_or(default.filename_aware),
+            collapse: self.collapse.unwrap_or(default.collapse),
+            elision: self.elision,
+            max_width: self.max_width,
+        };
+        validate(&cfg)?;
+
+        Ok(cfg)
+    }
+}
+
+/// Checks the values of a config that can't be used as is.
+pub(crate) fn validate(cfg: &Config) -> Result<(), Error> {
+    validate_delimiter(&cfg.delimiter)?;
+
+    let rule_strategies = cfg.rules.iter().filter_map(|rule| match &rule.action {
+        Action::Strategy(strategy) => Some(strategy),
+        _ => None,
+    });
+    if let Some(strategy) = core::iter::once(&cfg.strategy)
+        .chain(rule_strategies)
+        .find(|s| matches!(s, Strategy::FirstChars(0) | Strategy::LastChars(0)))
+    {
+        return Err(Error::ZeroLengthStrategy(strategy.to_string()));
+    }
+
+    if cfg.max_width == Some(0) {
+        return Err(Error::ZeroMaxWidth);
+    }
+
+    Ok(())
+}
+
+pub(crate) fn validate_delimiter(delimiter: &Delimiter) -> Result<(), Error> {
+    match delimiter {
+        Delimiter::Literal(literal) if literal.is_empty() => Err(Error::EmptyDelimiter),
+        Delimiter::Set(literals) if literals.is_empty() => Err(Error::EmptyDelimiterSet),
+        Delimiter::Set(literals) if literals.iter().any(|l| l.is_empty()) => {
+            Err(Error::EmptyDelimiter)
+        }
+        Delimiter::Regex(re) if re.is_match("") => {
+            Err(Error::DelimiterRegexMatchesEmpty(re.as_str().to_string()))
+        }
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shorten_line;
+    use alloc::vec;
+    use insta::assert_snapshot;
+
+    #[test]
+    fn building_with_no_values_matches_default_config() {
+        // GIVEN
+        let line = "/module/submodule/service/lib.rs";
+
+        // WHEN
+        let cfg = Config::builder().build().unwrap();
+
+        // THEN
+        assert_eq!(
+            shorten_line(&cfg, line),
+            shorten_line(&Config::default(), line)
+        );
+    }
+
+    #[test]
+    fn building_compiles_regexes() {
+        // GIVEN
+        let line = "module -> submodule => service -> lib.rs";
+
+        // WHEN
+        let cfg = Config::builder()
+            .delimiter_regex(r"\s*[-=]>\s*")
+            .ignore_regex("^sub")
+            .output_delimiter("/")
+            .build()
+            .unwrap();
+
+        // THEN
+        assert_eq!(
+            shorten_line(&cfg, line),
+            vec!["m", "submodule", "s", "lib.rs"]
+        );
+    }
+
+    #[test]
+    fn building_compiles_regexes_with_the_chosen_engine() {
+        for &engine in Engine::AVAILABLE {
+            // GIVEN
+            let line = "module -> submodule => service-billing-v2 -> lib.rs";
+
+            // WHEN
+            let cfg = Config::builder()
+                .engine(engine)
+                .delimiter_regex(r"\s*[-=]>\s*")
+                .ignore_regex("^sub")
+                .extraction(r"^service-(\w)\w*-(v\d+)$", "$1$2")
+                .build()
+                .unwrap();
+
+            // THEN
+            let regexes = [
+                cfg.ignore_regex.as_ref(),
+                cfg.extraction.as_ref().map(|e| &e.regex),
+            ];
+            assert!(regexes.iter().all(|re| re.unwrap().engine() == engine));
+            assert_eq!(
+                shorten_line(&cfg, line),
+                vec!["m", "submodule", "bv2", "lib.rs"]
+            );
+        }
+    }
+
+    #[test]
+    fn building_fails_for_empty_delimiter() {
+        // GIVEN
+        // WHEN
+        let literal = Config::builder().delimiter("").build().unwrap_err();
+        let in_set = Config::builder()
+            .delimiters(vec!["/", ""])
+            .build()
+            .unwrap_err();
+        let empty_set = Config::builder()
+            .delimiters(Vec::<&str>::new())
+            .build()
+            .unwrap_err();
+
+        // THEN
+        assert!(matches!(literal, Error::EmptyDelimiter));
+        assert!(matches!(in_set, Error::EmptyDelimiter));
+        assert!(matches!(empty_set, Error::EmptyDelimiterSet));
+    }
+
+    #[test]
+    fn building_splits_alias_sequences_on_the_delimiter() {
+        // GIVEN
+        // WHEN
+        let cfg = Config::builder()
+            .delimiters(vec!["/", "::"])
+            .alias("src/main::scala", "main")
+            .build()
+            .unwrap();
+
+        // THEN
+        assert_eq!(
+            cfg.aliases,
+            vec![Alias::new(["src", "main", "scala"], "main")]
+        );
+    }
+
+    #[test]
+    fn building_fails_for_delimiter_regex_matching_empty_string() {
+        // GIVEN
+        // WHEN
+        let err = Config::builder().delimiter_regex("/*").build().unwrap_err();
+
+        // THEN
+        assert_snapshot!(err, @r#"delimiter regex "/*" matches the empty string"#);
+    }
+
+    #[test]
+    fn building_fails_for_invalid_regexes() {
+        // GIVEN
+        // WHEN
+        let delimiter_err = Config::builder().delimiter_regex("[/").build().unwrap_err();
+        let ignore_err = Config::builder()
+            .ignore_regex("(module")
+            .build()
+            .unwrap_err();
+        let extraction_err = Config::builder()
+            .extraction(r"service-(\w", "$1")
+            .build()
+            .unwrap_err();
+
+        // THEN
+        assert!(matches!(delimiter_err, Error::InvalidDelimiterRegex(_)));
+        assert!(matches!(ignore_err, Error::InvalidIgnoreRegex(_)));
+        assert!(matches!(extraction_err, Error::InvalidExtractionRegex(_)));
+        assert!(core::error::Error::source(&ignore_err).is_some());
+    }
+
+    #[test]
+    fn building_fails_for_zero_length_strategies() {
+        // GIVEN
+        // WHEN
+        let err = Config::builder()
+            .strategy(Strategy::LastChars(0))
+            .build()
+            .unwrap_err();
+
+        // THEN
+        assert_snapshot!(err, @r#"strategy "last:0" would abbreviate components to nothing"#);
+    }
+
+    #[test]
+    fn building_fails_for_zero_max_width() {
+        // GIVEN
+        // WHEN
+        let err = Config::builder().max_width(0).build().unwrap_err();
+
+        // THEN
+        assert!(matches!(err, Error::ZeroMaxWidth));
+    }
+
+    #[test]
+    fn building_fails_for_zero_length_strategies_in_rules() {
+        // GIVEN
+        // WHEN
+        let err = Config::builder()
+            .rule("word:main => keep".parse().unwrap())
+            .rule("position:0 => strategy:first:0".parse().unwrap())
+            .build()
+            .unwrap_err();
+
+        // THEN
+        assert_snapshot!(err, @r#"strategy "first:0" would abbreviate components to nothing"#);
+    }
+}
@@ -0,0 +1,173 @@
+use crate::{Config, shorten_line_into};
+use alloc::borrow::Cow;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+/// Private use character that the byte `0x00` would stand in for; only bytes from `0x80` up (the
+/// only ones that can be invalid UTF-8) are escaped, as `U+10FF80` to `U+10FFFF`.
+const ESCAPE_BASE: u32 = 0x10_FF00;
+
+/// Shortens a line that may not be valid UTF-8 based on the provided configuration, and returns
+/// it joined as is done by [`shorten_line_into`].
+///
+/// Bytes that aren't valid UTF-8 are carried through unchanged: they're treated as single
+/// characters (that never match a delimiter, or are part of one) while shortening, and are output
+/// as they were (see [`escape_bytes`]). Lines that are valid UTF-8 are shortened as is.
+///
+/// Example:
+/// ```
+/// use squidge::{Config, shorten_bytes};
+///
+/// let cfg = Config::default();
+/// assert_eq!(shorten_bytes(&cfg, b"module/service/lib.rs"), b"m/s/lib.rs");
+/// assert_eq!(shorten_bytes(&cfg, b"\xffmodule/\xfeservice/lib.rs"), b"\xff/\xfe/lib.rs");
+/// ```
+pub fn shorten_bytes(cfg: &Config, line: &[u8]) -> Vec<u8> {
+    let line = escape_bytes(line);
+    let mut shortened = String::with_capacity(line.len());
+    // writing to a String never fails
+    let _ = shorten_line_into(cfg, &line, &mut shortened);
+
+    match line {
+        // nothing was escaped, so characters that look like escaped bytes are left as is
+        Cow::Borrowed(_) => shortened.into_bytes(),
+        Cow::Owned(_) => unescape_bytes(&shortened).into_owned(),
+    }
+}
+
+/// Decodes bytes as UTF-8, replacing each byte that isn't valid UTF-8 with a character from the
+/// end of the Supplementary Private Use Area-B (`U+10FF80` to `U+10FFFF`) that
+/// [`unescape_bytes`] turns back into it, much like Python's `surrogateescape` error handler.
+///
+/// Valid UTF-8 is borrowed as is, and needn't (and shouldn't) be unescaped. When bytes were
+/// escaped, characters from that range that were already in the text (as valid UTF-8) can't be
+/// told apart from escaped bytes once it's unescaped.
+///
+/// Example:
+/// ```
+/// use squidge::{escape_bytes, unescape_bytes};
+///
+/// let escaped = escape_bytes(b"caf\xe9/lib.rs");
+/// assert_eq!(escaped.chars().count(), "café/lib.rs".chars().count());
+/// assert_eq!(unescape_bytes(&escaped), &b"caf\xe9/lib.rs"[..]);
+/// ```
+pub fn escape_bytes(bytes: &[u8]) -> Cow<'_, str> {
+    if let Ok(text) = core::str::from_utf8(bytes) {
+        return Cow::Borrowed(text);
+    }
+
+    let mut escaped = String::with_capacity(bytes.len() * 2);
+    for chunk in bytes.utf8_chunks() {
+        escaped.push_str(chunk.valid());
+        escaped.extend(chunk.invalid().iter().filter_map(|&b| escape(b)));
+    }
+
+    Cow::Owned(escaped)
+}
+
+/// Encodes text as UTF-8, turning the characters that [`escape_bytes`] replaced bytes with back
+/// into them.
+///
+/// It's only meant for text that [`escape_bytes`] escaped bytes in (that is, returned an owned
+/// value for); text that was valid UTF-8 to begin with is to be encoded as is.
+pub fn unescape_bytes(text: &str) -> Cow<'_, [u8]> {
+    if !text.chars().any(|c| unescape(c).is_some()) {
+        return Cow::Borrowed(text.as_bytes());
+    }
+
+    let mut bytes = Vec::with_capacity(text.len());
+    for c in text.chars() {
+        match unescape(c) {
+            Some(b) => bytes.push(b),
+            None => bytes.extend_from_slice(c.encode_utf8(&mut [0; 4]).as_bytes()),
+        }
+    }
+
+    Cow::Owned(bytes)
+}
+
+/// Whether `c` is one of the characters that stand in for escaped bytes.
+pub(crate) fn is_escaped(c: char) -> bool {
+    unescape(c).is_some()
+}
+
+fn escape(byte: u8) -> Option<char> {
+    if byte < 0x80 {
+        return None;
+    }
+
+    char::from_u32(ESCAPE_BASE + u32::from(byte))
+}
+
+fn unescape(c: char) -> Option<u8> {
+    u32::from(c)
+        .checked_sub(ESCAPE_BASE)
+        .and_then(|b| u8::try_from(b).ok())
+        .filter(|&b| b >= 0x80)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Strategy;
+
+    #[test]
+    fn escaping_bytes_round_trips() {
+        // GIVEN
+        let lines: [&[u8]; 4] = [
+            b"module/service/lib.rs",
+            b"\xff\xfe/caf\xe9/\xe2\x82/lib.rs",
+            "ñ/\u{1F600}/é".as_bytes(),
+            b"",
+        ];
+
+        for line in lines {
+            // WHEN
+            let escaped = escape_bytes(line);
+            let result = unescape_bytes(&escaped);
+
+            // THEN
+            assert_eq!(result, line);
+        }
+    }
+
+    #[test]
+    fn shorten_bytes_passes_invalid_bytes_through() {
+        // GIVEN
+        let cfg = Config {
+            strategy: Strategy::LastChars(2),
+            ..Config::default()
+        };
+
+        // WHEN
+        let result = shorten_bytes(&cfg, b"home/caf\xe9/\xe2\x82\xffdata/ar\xc3chive.tar");
+
+        // THEN
+        assert_eq!(result, b"me/f\xe9/ta/ar\xc3chive.tar");
+    }
+
+    #[test]
+    fn shorten_bytes_leaves_valid_utf8_that_looks_escaped_as_is() {
+        // GIVEN
+        // U+10FF2F and U+10FFFF, which escaped '/' and 0xff would've looked like, respectively
+        let line = "dir/\u{10FF2F}x/\u{10FFFF}/file";
+
+        // WHEN
+        let result = shorten_bytes(&Config::default(), line.as_bytes());
+
+        // THEN
+        assert_eq!(result, "d/\u{10FF2F}/\u{10FFFF}/file".as_bytes());
+    }
+
+    #[test]
+    fn shorten_bytes_never_splits_on_escaped_bytes() {
+        // GIVEN
+        let cfg = Config::builder().delimiter_regex("[^a-z]").build().unwrap();
+
+        // WHEN
+        let result = shorten_bytes(&cfg, b"ab\xffcd/ef\xfe/gh");
+
+        // THEN
+        assert_eq!(result, b"a/e/gh");
+    }
+}
@@ -0,0 +1,285 @@
+use crate::width::{Measure, display_width};
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt;
+use core::str::FromStr;
+
+/// Marker placed between a collapsed component (or block) and the number of times it repeats.
+const REPEAT_MARKER: &str = "×";
+
+/// Determines whether repeated components are collapsed into a single annotated token.
+///
+/// Repetitions are detected on the original components (after prefixes and aliases are applied),
+/// and are replaced by their first occurrence, shortened as usual, followed by `×` and the number
+/// of times it repeats. Blocks of several components are wrapped in parentheses.
+///
+/// Example:
+/// ```
+/// use squidge::{Collapse, Config, shorten_line};
+///
+/// let runs = Config {
+///     collapse: Collapse::Runs,
+///     ..Config::default()
+/// };
+/// assert_eq!(
+///     shorten_line(&runs, "build/build/build/out/lib.rs"),
+///     vec!["b×3", "o", "lib.rs"]
+/// );
+///
+/// let blocks = Config {
+///     collapse: Collapse::Blocks,
+///     ..Config::default()
+/// };
+/// assert_eq!(
+///     shorten_line(&blocks, "com/example/com/example/Foo.java"),
+///     vec!["(c/e)×2", "Foo.java"]
+/// );
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Collapse {
+    /// Repeated components are left as is
+    #[default]
+    Off,
+    /// Runs of identical components are collapsed (`build/build/build` -> `b×3`)
+    Runs,
+    /// Runs of identical blocks of one or more components are collapsed
+    /// (`com/example/com/example` -> `(c/e)×2`)
+    Blocks,
+}
+
+impl fmt::Display for Collapse {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Collapse::Off => write!(f, "off"),
+            Collapse::Runs => write!(f, "runs"),
+            Collapse::Blocks => write!(f, "blocks"),
+        }
+    }
+}
+
+/// Error returned when parsing a [`Collapse`] from a string fails.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseCollapseError(String);
+
+impl fmt::Display for ParseCollapseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            r#"invalid collapse mode "{}" (possible values: off, runs, blocks)"#,
+            self.0
+        )
+    }
+}
+
+impl core::error::Error for ParseCollapseError {}
+
+impl FromStr for Collapse {
+    type Err = ParseCollapseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "off" => Ok(Collapse::Off),
+            "runs" => Ok(Collapse::Runs),
+            "blocks" => Ok(Collapse::Blocks),
+            _ => Err(ParseCollapseError(s.to_string())),
+        }
+    }
+}
+
+/// A block of `len` parts, starting at index `start`, that appears `count` times in a row.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Repeat {
+    pub(crate) start: usize,
+    pub(crate) len: usize,
+    pub(crate) count: usize,
+}
+
+impl Collapse {
+    /// Finds the repeats to collapse in `parts`, in order.
+    ///
+    /// Repeats are looked for from left to right; at each position, the block covering the most
+    /// parts wins, the shorter block winning ties (so `a/a/a/a` is `a×4` rather than `(a/a)×2`).
+    pub(crate) fn repeats<T: PartialEq>(self, parts: &[T]) -> Vec<Repeat> {
+        let mut repeats = vec![];
+        let mut start = 0;
+
+        while start < parts.len() {
+            let max_len = match self {
+                Collapse::Off => return repeats,
+                Collapse::Runs => 1,
+                Collapse::Blocks => (parts.len() - start) / 2,
+            };
+
+            let best = (1..=max_len)
+                .map(|len| {
+                    let block = &parts[start..start + len];
+                    let count = parts[start..]
+                        .chunks_exact(len)
+                        .take_while(|chunk| *chunk == block)
+                        .count();
+                    Repeat { start, len, count }
+                })
+                .filter(|repeat| repeat.count > 1)
+                .rev()
+                .max_by_key(|repeat| repeat.len * repeat.count);
+
+            match best {
+                Some(repeat) => {
+                    start = repeat.end();
+                    repeats.push(repeat);
+                }
+                None => start += 1,
+            }
+        }
+
+        repeats
+    }
+}
+
+impl Repeat {
+    /// The index right after the last repetition.
+    pub(crate) fn end(&self) -> usize {
+        self.start + self.len * self.count
+    }
+
+    /// Whether the part at index `i` is in the first occurrence of the block.
+    pub(crate) fn is_first_occurrence(&self, i: usize) -> bool {
+        (self.start..self.start + self.len).contains(&i)
+    }
+
+    /// Adjusts the measure of the part at index `i` (which is part of the repeat): later
+    /// repetitions are left out, and the annotation is attributed to the end of the first one.
+    pub(crate) fn measure(&self, i: usize, measure: Measure) -> Measure {
+        match measure {
+            _ if !self.is_first_occurrence(i) => Measure::Dropped,
+            Measure::Kept { width, abbreviated } if i == self.start + self.len - 1 => {
+                let extra = display_width(&self.annotate("")) + if self.len > 1 { 2 } else { 0 };
+                Measure::Kept {
+                    width: width + extra,
+                    abbreviated: abbreviated.map(|a| a + extra),
+                }
+            }
+            _ => measure,
+        }
+    }
+
+    /// Annotates the (joined) first occurrence of the block with the number of repetitions.
+    pub(crate) fn annotate(&self, block: &str) -> String {
+        format!("{block}{REPEAT_MARKER}{}", self.count)
+    }
+}
+
+/// Joins the kept parts of a block's first occurrence (each with the delimiter that followed it)
+/// and annotates them; `None` if every part of the block is dropped.
+pub(crate) fn collapse<S: AsRef<str>>(
+    repeat: &Repeat,
+    block: &[(S, Option<&str>)],
+    joining_delimiter: Option<&str>,
+) -> Option<String> {
+    match block {
+        [] => None,
+        [(component, _)] => Some(repeat.annotate(component.as_ref())),
+        [.., (last, _)] => {
+            let mut joined = String::from("(");
+            for (component, delimiter) in &block[..block.len() - 1] {
+                joined.push_str(component.as_ref());
+                if let Some(delimiter) = delimiter {
+                    joined.push_str(joining_delimiter.unwrap_or(delimiter));
+                }
+            }
+            joined.push_str(last.as_ref());
+            joined.push(')');
+            Some(repeat.annotate(&joined))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use insta::assert_yaml_snapshot;
+
+    fn describe(collapse: Collapse, line: &str) -> Vec<String> {
+        let parts: Vec<_> = line.split('/').collect();
+        collapse
+            .repeats(&parts)
+            .iter()
+            .map(|r| {
+                format!(
+                    "{}×{} at {}",
+                    parts[r.start..r.start + r.len].join("/"),
+                    r.count,
+                    r.start
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn repeats_finds_runs_of_identical_parts() {
+        // GIVEN
+        let lines = ["build/build/build/out", "a/b/b/c/c/d", "a/b/a/b", "a"];
+
+        // WHEN
+        let result: Vec<_> = lines
+            .iter()
+            .map(|line| describe(Collapse::Runs, line))
+            .collect();
+
+        // THEN
+        assert_yaml_snapshot!(result, @r"
+        - - build×3 at 0
+        - - b×2 at 1
+          - c×2 at 3
+        - []
+        - []
+        ");
+    }
+
+    #[test]
+    fn repeats_finds_the_block_covering_the_most_parts() {
+        // GIVEN
+        let lines = [
+            "com/example/com/example/Foo",
+            "a/a/a/a",
+            "x/a/b/a/b/a/b/a",
+            "a/b/c/a/b/c/a/b",
+            "a/a/b/a/a/b",
+        ];
+
+        // WHEN
+        let result: Vec<_> = lines
+            .iter()
+            .map(|line| describe(Collapse::Blocks, line))
+            .collect();
+
+        // THEN
+        assert_yaml_snapshot!(result, @r"
+        - - com/example×2 at 0
+        - - a×4 at 0
+        - - a/b×3 at 1
+        - - a/b/c×2 at 0
+        - - a/a/b×2 at 0
+        ");
+    }
+
+    #[test]
+    fn parsing_collapse_modes_works() {
+        // GIVEN
+        // WHEN
+        let parsed: Vec<_> = ["off", "runs", "blocks"]
+            .into_iter()
+            .map(|s| s.parse::<Collapse>().unwrap().to_string())
+            .collect();
+        let err = "all".parse::<Collapse>().unwrap_err();
+
+        // THEN
+        assert_eq!(parsed, vec!["off", "runs", "blocks"]);
+        assert_eq!(
+            err.to_string(),
+            r#"invalid collapse mode "all" (possible values: off, runs, blocks)"#
+        );
+    }
+}
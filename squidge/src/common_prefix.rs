@@ -0,0 +1,153 @@
+use crate::Config;
+use crate::delimiter;
+use alloc::vec;
+use alloc::vec::Vec;
+
+/// Lines split into the prefix of components they all share, and what follows it.
+///
+/// Returned by [`split_common_prefix`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CommonPrefix<'l> {
+    /// The shared prefix, as it appears in the lines (without the delimiter that follows it)
+    pub prefix: &'l str,
+    /// The delimiter that follows the shared prefix; `None` if the lines share no prefix
+    pub delimiter: Option<&'l str>,
+    /// What follows the shared prefix (and its delimiter) in each line
+    pub remainders: Vec<&'l str>,
+}
+
+/// Finds the longest prefix of components (and the delimiters between them) shared by all lines,
+/// and splits each line into that prefix and its remainder.
+///
+/// The last component of a line is never part of the prefix, so every remainder has at least one
+/// component. The remainders can then be shortened as usual, to avoid repeating the prefix on every
+/// line; note that [`Config::prefixes`] and [`Config::aliases`] only apply to what's shortened.
+///
+/// Example:
+/// ```
+/// use squidge::{Config, split_common_prefix, shorten_line};
+///
+/// let cfg = Config::default();
+/// let lines = [
+///     "src/main/scala/admin/billing/Server.scala",
+///     "src/main/scala/admin/backup/Components.scala",
+/// ];
+/// let common = split_common_prefix(&cfg, &lines);
+/// assert_eq!(common.prefix, "src/main/scala/admin");
+/// assert_eq!(common.delimiter, Some("/"));
+/// assert_eq!(
+///     common.remainders,
+///     vec!["billing/Server.scala", "backup/Components.scala"]
+/// );
+/// assert_eq!(
+///     shorten_line(&cfg, common.remainders[0]),
+///     vec!["b", "Server.scala"]
+/// );
+/// ```
+pub fn split_common_prefix<'l, L: AsRef<str>>(cfg: &Config, lines: &'l [L]) -> CommonPrefix<'l> {
+    let Some(first) = lines.first().map(AsRef::as_ref) else {
+        return CommonPrefix::default();
+    };
+
+    let mut splits: Vec<_> = lines.iter().map(|line| cfg.split(line.as_ref())).collect();
+    // where the remainder starts in each line
+    let mut offsets = vec![0; lines.len()];
+    let mut prefix_end = 0;
+    let mut delimiter = None;
+
+    while let Some(parts) = splits
+        .iter_mut()
+        .map(Iterator::next)
+        .collect::<Option<Vec<_>>>()
+    {
+        let (_, Some(following)) = parts[0] else {
+            break;
+        };
+        if parts.iter().any(|part| *part != parts[0]) {
+            break;
+        }
+
+        prefix_end = delimiter::offset(first, following);
+        delimiter = Some(following);
+        // offsets come from the delimiters, since quoted components don't span their quotes
+        let delimiters = parts.iter().filter_map(|(_, following)| *following);
+        for ((offset, line), following) in offsets.iter_mut().zip(lines).zip(delimiters) {
+            *offset = delimiter::offset(line.as_ref(), following) + following.len();
+        }
+    }
+
+    CommonPrefix {
+        prefix: &first[..prefix_end],
+        delimiter,
+        remainders: lines
+            .iter()
+            .zip(offsets)
+            .map(|(line, offset)| &line.as_ref()[offset..])
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Delimiter;
+    use insta::assert_yaml_snapshot;
+
+    #[test]
+    fn split_common_prefix_never_includes_the_last_component() {
+        // GIVEN
+        let lines = ["/src/main/lib.rs", "/src/main/lib.rs", "/src/main"];
+
+        // WHEN
+        let result = split_common_prefix(&Config::default(), &lines);
+
+        // THEN
+        assert_eq!(
+            result,
+            CommonPrefix {
+                prefix: "/src",
+                delimiter: Some("/"),
+                remainders: vec!["main/lib.rs", "main/lib.rs", "main"],
+            }
+        );
+    }
+
+    #[test]
+    fn split_common_prefix_compares_delimiters_too() {
+        // GIVEN
+        let cfg = Config {
+            delimiter: Delimiter::from(vec!["/", "::"]),
+            ..Config::default()
+        };
+        let lines = ["crate::module/service::lib", "crate::module::service/lib"];
+
+        // WHEN
+        let result = split_common_prefix(&cfg, &lines);
+
+        // THEN
+        assert_yaml_snapshot!(result.remainders, @r#"
+        - "module/service::lib"
+        - "module::service/lib"
+        "#);
+        assert_eq!(result.prefix, "crate");
+    }
+
+    #[test]
+    fn split_common_prefix_works_when_nothing_is_shared() {
+        // GIVEN
+        let lines = ["src/lib.rs", "tests/cli.rs"];
+
+        // WHEN
+        let result = split_common_prefix(&Config::default(), &lines);
+
+        // THEN
+        assert_eq!(
+            result,
+            CommonPrefix {
+                prefix: "",
+                delimiter: None,
+                remainders: vec!["src/lib.rs", "tests/cli.rs"],
+            }
+        );
+    }
+}
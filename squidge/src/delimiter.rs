@@ -0,0 +1,427 @@
+use crate::{Quoting, Regex, bytes};
+use alloc::borrow::Cow;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+/// Determines where a line is split into components.
+///
+/// Example:
+/// ```
+/// use squidge::{Config, Delimiter, Regex, shorten_line};
+///
+/// let line = "com.example/service::Handler";
+///
+/// let cfg = Config {
+///     delimiter: Delimiter::from(vec!["/", ".", "::"]),
+///     ..Config::default()
+/// };
+/// assert_eq!(shorten_line(&cfg, line), vec!["c", "e", "s", "Handler"]);
+///
+/// let cfg = Config {
+///     delimiter: Delimiter::Regex(Regex::new(r"[/.]|::").unwrap()),
+///     ..Config::default()
+/// };
+/// assert_eq!(shorten_line(&cfg, line), vec!["c", "e", "s", "Handler"]);
+/// ```
+#[derive(Debug, Clone)]
+pub enum Delimiter {
+    /// Split on every occurrence of a string
+    Literal(String),
+    /// Split on every occurrence of any of the strings; when several of them match at the same
+    /// position, the longest one is used
+    Set(Vec<String>),
+    /// Split on every match of a regex
+    Regex(Regex),
+}
+
+impl From<&str> for Delimiter {
+    fn from(value: &str) -> Self {
+        Delimiter::Literal(value.to_string())
+    }
+}
+
+impl From<String> for Delimiter {
+    fn from(value: String) -> Self {
+        Delimiter::Literal(value)
+    }
+}
+
+impl From<Vec<&str>> for Delimiter {
+    fn from(value: Vec<&str>) -> Self {
+        Delimiter::Set(value.into_iter().map(String::from).collect())
+    }
+}
+
+impl From<Vec<String>> for Delimiter {
+    fn from(value: Vec<String>) -> Self {
+        Delimiter::Set(value)
+    }
+}
+
+impl From<Regex> for Delimiter {
+    fn from(value: Regex) -> Self {
+        Delimiter::Regex(value)
+    }
+}
+
+/// Regex delimiters are compared by their patterns and engines.
+impl PartialEq for Delimiter {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Delimiter::Literal(a), Delimiter::Literal(b)) => a == b,
+            (Delimiter::Set(a), Delimiter::Set(b)) => a == b,
+            (Delimiter::Regex(a), Delimiter::Regex(b)) => a.key() == b.key(),
+            _ => false,
+        }
+    }
+}
+
+impl Delimiter {
+    /// Splits `line` into components, each paired with the delimiter that followed it (the last
+    /// component has none).
+    pub(crate) fn split<'d, 'l>(&'d self, line: &'l str) -> Split<'d, 'l> {
+        Split {
+            delimiter: self,
+            line,
+            start: Some(0),
+            search_from: 0,
+            skip_empty: false,
+            upcoming: None,
+            quoting: None,
+            escaped: line.contains(bytes::is_escaped),
+        }
+    }
+
+    /// Whether a non-empty delimiter occurs in `text`.
+    pub(crate) fn matches_in(&self, text: &str) -> bool {
+        let escaped = text.contains(bytes::is_escaped);
+        let mut from = 0;
+        while let Some((start, end)) = self.find_at(text, from, escaped) {
+            if start != end {
+                return true;
+            }
+            from = end + text[end..].chars().next().map_or(1, char::len_utf8);
+        }
+
+        false
+    }
+
+    /// Returns the byte range of the first delimiter in `haystack` that starts at or after
+    /// `from`. If `haystack` has bytes escaped by [`escape_bytes`](crate::escape_bytes) in it,
+    /// delimiters are only looked for in the text between them, so they never match (or are part
+    /// of) a delimiter.
+    fn find_at(&self, haystack: &str, from: usize, escaped: bool) -> Option<(usize, usize)> {
+        if !escaped {
+            return self.find_in(haystack, from);
+        }
+
+        let mut from = from;
+        while from <= haystack.len() {
+            let Some((i, byte)) = haystack[from..]
+                .char_indices()
+                .find(|&(_, c)| bytes::is_escaped(c))
+            else {
+                return self.find_in(haystack, from);
+            };
+            if let Some(found) = self.find_in(&haystack[..from + i], from) {
+                return Some(found);
+            }
+            from += i + byte.len_utf8();
+        }
+
+        None
+    }
+
+    fn find_in(&self, haystack: &str, from: usize) -> Option<(usize, usize)> {
+        if from > haystack.len() {
+            return None;
+        }
+
+        match self {
+            Delimiter::Literal(literal) => find_literal(haystack, from, literal),
+            Delimiter::Set(literals) => literals
+                .iter()
+                .filter_map(|literal| find_literal(haystack, from, literal))
+                .min_by_key(|&(start, end)| (start, core::cmp::Reverse(end))),
+            Delimiter::Regex(re) => re.find_at(haystack, from),
+        }
+    }
+}
+
+fn find_literal(haystack: &str, from: usize, literal: &str) -> Option<(usize, usize)> {
+    haystack[from..]
+        .find(literal)
+        .map(|i| (from + i, from + i + literal.len()))
+}
+
+/// Returns the byte offset of `part`, a slice of `line`, within it.
+pub(crate) fn offset(line: &str, part: &str) -> usize {
+    part.as_ptr() as usize - line.as_ptr() as usize
+}
+
+/// Returns the text of `line` from the start of `first` to the end of `last`, both of which are
+/// slices of it.
+pub(crate) fn between<'l>(line: &'l str, first: &str, last: &str) -> &'l str {
+    &line[offset(line, first)..offset(line, last) + last.len()]
+}
+
+/// Iterator over the components of a line, along with the delimiters that follow them.
+#[derive(Debug, Clone)]
+pub(crate) struct Split<'d, 'l> {
+    delimiter: &'d Delimiter,
+    line: &'l str,
+    start: Option<usize>,
+    search_from: usize,
+    skip_empty: bool,
+    // the next non-empty component, read ahead when skipping empty ones
+    upcoming: Option<(Cow<'l, str>, Option<&'l str>, &'l str)>,
+    quoting: Option<&'d Quoting>,
+    escaped: bool,
+}
+
+impl<'d, 'l> Split<'d, 'l> {
+    /// Leaves out empty components; the delimiter following a component is left out too if only
+    /// empty components follow it.
+    pub(crate) fn skip_empty(mut self) -> Self {
+        self.skip_empty = true;
+        self
+    }
+
+    /// Doesn't split components quoted as per `quoting`, and yields the text between their
+    /// quotes, unescaped.
+    pub(crate) fn quoted(mut self, quoting: Option<&'d Quoting>) -> Self {
+        self.quoting = quoting;
+        self
+    }
+
+    /// Returns the text of the line from the start of `first` to the end of `last`, both of
+    /// which are sources of components yielded by this split.
+    pub(crate) fn between(&self, first: &'l str, last: &'l str) -> &'l str {
+        between(self.line, first, last)
+    }
+
+    /// Returns the next component, along with the delimiter that follows it, and the text of the
+    /// line it comes from.
+    pub(crate) fn next_with_source(&mut self) -> Option<(Cow<'l, str>, Option<&'l str>, &'l str)> {
+        if !self.skip_empty {
+            return self.next_component();
+        }
+
+        let (component, delimiter, source) = match self.upcoming.take() {
+            Some(upcoming) => upcoming,
+            None => self.next_non_empty()?,
+        };
+        // the delimiter is only kept if a non-empty component follows it
+        self.upcoming = self.next_non_empty();
+        let delimiter = self.upcoming.as_ref().and(delimiter);
+
+        Some((component, delimiter, source))
+    }
+
+    fn next_non_empty(&mut self) -> Option<(Cow<'l, str>, Option<&'l str>, &'l str)> {
+        core::iter::from_fn(|| self.next_component()).find(|(c, _, _)| !c.is_empty())
+    }
+
+    fn next_component(&mut self) -> Option<(Cow<'l, str>, Option<&'l str>, &'l str)> {
+        let start = self.start?;
+        let (line, quoting) = (self.line, self.quoting);
+        let closing = quoting
+            .and_then(|quoting| quoting.closing(&line[start..]))
+            .map(|end| start + end);
+        // delimiters within quotes don't count
+        let search_from = closing.map_or(self.search_from, |end| end.max(self.search_from));
+        // the text between the quotes, if nothing but a delimiter follows the closing quote
+        let component = |end: usize| match (quoting, closing) {
+            (Some(quoting), Some(closing)) if closing == end => {
+                let quote_len = quoting.quote.len_utf8();
+                let text = &line[start + quote_len..closing - quote_len];
+                (quoting.unescape(text), text)
+            }
+            _ => (Cow::Borrowed(&line[start..end]), &line[start..end]),
+        };
+
+        match self.delimiter.find_at(self.line, search_from, self.escaped) {
+            Some((delimiter_start, delimiter_end)) => {
+                self.start = Some(delimiter_end);
+                // empty delimiters would otherwise match at the same position forever
+                self.search_from = if delimiter_start == delimiter_end {
+                    delimiter_end
+                        + self.line[delimiter_end..]
+                            .chars()
+                            .next()
+                            .map_or(1, char::len_utf8)
+                } else {
+                    delimiter_end
+                };
+
+                let (component, source) = component(delimiter_start);
+                Some((
+                    component,
+                    Some(&line[delimiter_start..delimiter_end]),
+                    source,
+                ))
+            }
+            None => {
+                self.start = None;
+                let (component, source) = component(line.len());
+                Some((component, None, source))
+            }
+        }
+    }
+}
+
+impl<'l> Iterator for Split<'_, 'l> {
+    type Item = (Cow<'l, str>, Option<&'l str>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_with_source()
+            .map(|(component, delimiter, _)| (component, delimiter))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+    use insta::assert_yaml_snapshot;
+
+    #[test]
+    fn literal_split_matches_str_split() {
+        // GIVEN
+        let lines = ["/path/to/lib.rs", "/////", "lib.rs", "", "ñ/é/"];
+
+        for line in lines {
+            // WHEN
+            let result: Vec<_> = Delimiter::from("/").split(line).map(|(c, _)| c).collect();
+
+            // THEN
+            assert_eq!(result, line.split("/").collect::<Vec<_>>());
+        }
+    }
+
+    #[test]
+    fn empty_literal_split_matches_str_split() {
+        // GIVEN
+        let lines = ["/path", "", "ñé"];
+
+        for line in lines {
+            // WHEN
+            let result: Vec<_> = Delimiter::from("").split(line).map(|(c, _)| c).collect();
+
+            // THEN
+            assert_eq!(result, line.split("").collect::<Vec<_>>());
+        }
+    }
+
+    #[test]
+    fn split_pairs_components_with_following_delimiters() {
+        // GIVEN
+        let delimiter = Delimiter::from("::");
+
+        // WHEN
+        let result: Vec<_> = delimiter.split("src::main::lib.rs").collect();
+
+        // THEN
+        assert_yaml_snapshot!(result, @r#"
+        - - src
+          - "::"
+        - - main
+          - "::"
+        - - lib.rs
+          - ~
+        "#);
+    }
+
+    #[test]
+    fn set_split_prefers_the_longest_delimiter() {
+        // GIVEN
+        let delimiter = Delimiter::from(vec![":", "/", "::", "."]);
+
+        // WHEN
+        let result: Vec<_> = delimiter.split("com.example/service::Handler:1").collect();
+
+        // THEN
+        assert_yaml_snapshot!(result, @r#"
+        - - com
+          - "."
+        - - example
+          - /
+        - - service
+          - "::"
+        - - Handler
+          - ":"
+        - - "1"
+          - ~
+        "#);
+    }
+
+    #[test]
+    fn regex_split_works() {
+        // GIVEN
+        let delimiter = Delimiter::from(Regex::new(r"\s*[|,]\s*").unwrap());
+
+        // WHEN
+        let result: Vec<_> = delimiter
+            .split("module | submodule,service ,lib.rs")
+            .collect();
+
+        // THEN
+        assert_yaml_snapshot!(result, @r#"
+        - - module
+          - " | "
+        - - submodule
+          - ","
+        - - service
+          - " ,"
+        - - lib.rs
+          - ~
+        "#);
+    }
+
+    #[test]
+    fn quoted_split_does_not_split_quoted_components() {
+        // GIVEN
+        let delimiter = Delimiter::from(",");
+        let quoting = Quoting::default();
+
+        // WHEN
+        let result: Vec<_> = delimiter
+            .split(r#""Smith, John",,"R&D, ""Berlin""""#)
+            .quoted(Some(&quoting))
+            .collect();
+
+        // THEN
+        assert_yaml_snapshot!(result, @r#"
+        - - "Smith, John"
+          - ","
+        - - ""
+          - ","
+        - - "R&D, \"Berlin\""
+          - ~
+        "#);
+    }
+
+    #[test]
+    fn split_skipping_empty_components_leaves_out_trailing_delimiters() {
+        // GIVEN
+        let delimiter = Delimiter::from("/");
+        let lines = ["//src//lib.rs//", "///", "src/"];
+
+        // WHEN
+        let result: Vec<Vec<_>> = lines
+            .iter()
+            .map(|line| delimiter.split(line).skip_empty().collect())
+            .collect();
+
+        // THEN
+        assert_yaml_snapshot!(result, @r"
+        - - - src
+            - /
+          - - lib.rs
+            - ~
+        - []
+        - - - src
+            - ~
+        ");
+    }
+}
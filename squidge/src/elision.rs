@@ -0,0 +1,138 @@
+use crate::collapse::Repeat;
+use crate::width::Measure;
+use alloc::string::{String, ToString};
+use core::ops::Range;
+
+/// Placeholder in [`Elision::marker`] that's replaced with the number of elided components.
+const COUNT_PLACEHOLDER: &str = "{count}";
+
+/// Keeps only the first and last few components of deep lines, replacing the ones in between with
+/// a marker.
+///
+/// Elision is applied after prefixes and aliases, and the components that are kept are handled as
+/// usual; `ignore_first_n`, `ignore_last_n` and positions in rules still refer to components by
+/// their place in the whole line. Components that would be dropped anyway aren't counted as
+/// elided, and repeated components are only collapsed outside of the elided ones.
+///
+/// Example:
+/// ```
+/// use squidge::{Config, Elision, shorten_line};
+///
+/// let cfg = Config {
+///     elision: Some(Elision {
+///         keep_first: 1,
+///         keep_last: 1,
+///         marker: "…({count})".to_string(),
+///     }),
+///     ..Config::default()
+/// };
+/// let result = shorten_line(&cfg, "src/main/scala/admin/b/c/d/e/f/Server.scala");
+/// assert_eq!(result, vec!["s", "…(8)", "Server.scala"]);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct Elision {
+    /// Number of components to keep from the start
+    pub keep_first: usize,
+    /// Number of components to keep from the end
+    pub keep_last: usize,
+    /// What the elided components are replaced with; `{count}` is replaced with their number
+    pub marker: String,
+}
+
+/// The components elided from a line, and what they're replaced with.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Elided {
+    pub(crate) range: Range<usize>,
+    pub(crate) marker: String,
+}
+
+impl Elision {
+    /// Determines which of `num_parts` parts are elided; `omitted` counts how many of the parts in
+    /// a range would otherwise be output. Nothing is elided if none of them would be.
+    pub(crate) fn elide<F>(&self, num_parts: usize, omitted: F) -> Option<Elided>
+    where
+        F: FnOnce(Range<usize>) -> usize,
+    {
+        let end = num_parts.checked_sub(self.keep_last)?;
+        if self.keep_first >= end {
+            return None;
+        }
+
+        let range = self.keep_first..end;
+        match omitted(range.clone()) {
+            0 => None,
+            n => Some(Elided {
+                range,
+                marker: self.marker.replace(COUNT_PLACEHOLDER, &n.to_string()),
+            }),
+        }
+    }
+}
+
+impl Default for Elision {
+    fn default() -> Self {
+        Elision {
+            keep_first: 1,
+            keep_last: 1,
+            marker: "…".to_string(),
+        }
+    }
+}
+
+impl Elided {
+    /// Adjusts the measure of the part at index `i`: elided parts are left out, except for the last
+    /// one, which stands for the marker.
+    pub(crate) fn measure(&self, i: usize, measure: Measure) -> Measure {
+        match i {
+            _ if !self.range.contains(&i) => measure,
+            _ if i == self.range.end - 1 => Measure::of(&self.marker, None),
+            _ => Measure::Dropped,
+        }
+    }
+
+    /// Whether any part of `repeat` is elided.
+    pub(crate) fn overlaps(&self, repeat: &Repeat) -> bool {
+        repeat.start < self.range.end && self.range.start < repeat.end()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::format;
+    use alloc::vec::Vec;
+    use insta::assert_yaml_snapshot;
+
+    #[test]
+    fn elide_only_elides_when_something_is_omitted() {
+        // GIVEN
+        let elision = Elision {
+            keep_first: 2,
+            keep_last: 1,
+            marker: "…({count})".to_string(),
+        };
+
+        // WHEN
+        let result: Vec<_> = (0..6)
+            .map(|num_parts| {
+                elision
+                    .elide(num_parts, |range| range.len())
+                    .map(|elided| format!("{:?} -> {}", elided.range, elided.marker))
+            })
+            .collect();
+        let all_dropped = elision.elide(6, |_| 0);
+
+        // THEN
+        assert_yaml_snapshot!(result, @r"
+        - ~
+        - ~
+        - ~
+        - ~
+        - 2..3 -> …(1)
+        - 2..4 -> …(2)
+        ");
+        assert_eq!(all_dropped, None);
+    }
+}
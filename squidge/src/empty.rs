@@ -0,0 +1,123 @@
+use alloc::string::{String, ToString};
+use core::fmt;
+use core::str::FromStr;
+
+/// Determines what happens to empty components, ie, the ones between consecutive delimiters, or
+/// before a leading (or after a trailing) delimiter.
+///
+/// Dropped empty components are left out before anything else happens, so they don't count
+/// towards `ignore_first_n`, `ignore_last_n`, or positions in rules; placeholders count as single
+/// components, and are kept as is.
+///
+/// Example:
+/// ```
+/// use squidge::{Config, Delimiter, EmptyComponents, shorten_line_into};
+///
+/// let line = ",module,,service,lib.rs,";
+/// let shorten = |empty_components| {
+///     let cfg = Config {
+///         delimiter: Delimiter::from(","),
+///         output_delimiter: ",".to_string(),
+///         ignore_last_n: 0,
+///         empty_components,
+///         ..Config::default()
+///     };
+///     let mut buffer = String::new();
+///     shorten_line_into(&cfg, line, &mut buffer).unwrap();
+///     buffer
+/// };
+///
+/// assert_eq!(shorten(EmptyComponents::Keep), ",m,,s,l,");
+/// assert_eq!(shorten(EmptyComponents::Drop), "m,s,l");
+/// assert_eq!(
+///     shorten(EmptyComponents::Placeholder("_".to_string())),
+///     "_,m,_,s,l,_"
+/// );
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum EmptyComponents {
+    /// Empty components are kept as is
+    #[default]
+    Keep,
+    /// Empty components are left out, along with the delimiters next to them
+    Drop,
+    /// Empty components are replaced with the given text
+    Placeholder(String),
+}
+
+impl fmt::Display for EmptyComponents {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EmptyComponents::Keep => write!(f, "keep"),
+            EmptyComponents::Drop => write!(f, "drop"),
+            EmptyComponents::Placeholder(placeholder) => write!(f, "placeholder:{placeholder}"),
+        }
+    }
+}
+
+/// Error returned when parsing [`EmptyComponents`] from a string fails.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseEmptyComponentsError(String);
+
+impl fmt::Display for ParseEmptyComponentsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            r#"invalid empty component policy "{}" (possible values: keep, drop, placeholder:TEXT)"#,
+            self.0
+        )
+    }
+}
+
+impl core::error::Error for ParseEmptyComponentsError {}
+
+impl FromStr for EmptyComponents {
+    type Err = ParseEmptyComponentsError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "keep" => Ok(EmptyComponents::Keep),
+            "drop" => Ok(EmptyComponents::Drop),
+            _ => match s.strip_prefix("placeholder:") {
+                Some(placeholder) if !placeholder.is_empty() => {
+                    Ok(EmptyComponents::Placeholder(placeholder.to_string()))
+                }
+                _ => Err(ParseEmptyComponentsError(s.to_string())),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+    use alloc::vec::Vec;
+
+    #[test]
+    fn parsing_empty_component_policies_works() {
+        // GIVEN
+        // WHEN
+        let parsed: Vec<_> = ["keep", "drop", "placeholder:∅", "placeholder::"]
+            .into_iter()
+            .map(|s| s.parse::<EmptyComponents>().unwrap().to_string())
+            .collect();
+        let errs: Vec<_> = ["skip", "placeholder:"]
+            .into_iter()
+            .map(|s| s.parse::<EmptyComponents>().unwrap_err().to_string())
+            .collect();
+
+        // THEN
+        assert_eq!(
+            parsed,
+            vec!["keep", "drop", "placeholder:∅", "placeholder::"]
+        );
+        assert_eq!(
+            errs,
+            vec![
+                r#"invalid empty component policy "skip" (possible values: keep, drop, placeholder:TEXT)"#,
+                r#"invalid empty component policy "placeholder:" (possible values: keep, drop, placeholder:TEXT)"#,
+            ]
+        );
+    }
+}
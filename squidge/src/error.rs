@@ -0,0 +1,56 @@
+use crate::RegexError;
+use alloc::string::String;
+use core::fmt;
+
+/// Errors returned while building a [`Config`](crate::Config).
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Error {
+    /// A delimiter (or one of the delimiters in a set) is empty
+    EmptyDelimiter,
+    /// A set of delimiters has no delimiters in it
+    EmptyDelimiterSet,
+    /// The delimiter regex couldn't be compiled
+    InvalidDelimiterRegex(RegexError),
+    /// The delimiter regex matches the empty string, which would split every character apart
+    DelimiterRegexMatchesEmpty(String),
+    /// The ignore regex couldn't be compiled
+    InvalidIgnoreRegex(RegexError),
+    /// The extraction regex couldn't be compiled
+    InvalidExtractionRegex(RegexError),
+    /// The abbreviation strategy would reduce components to nothing
+    ZeroLengthStrategy(String),
+    /// The width budget is zero
+    ZeroMaxWidth,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::EmptyDelimiter => write!(f, "delimiter cannot be empty"),
+            Error::EmptyDelimiterSet => write!(f, "at least one delimiter needs to be provided"),
+            Error::InvalidDelimiterRegex(_) => write!(f, "couldn't compile delimiter regex"),
+            Error::DelimiterRegexMatchesEmpty(pattern) => {
+                write!(f, r#"delimiter regex "{pattern}" matches the empty string"#)
+            }
+            Error::InvalidIgnoreRegex(_) => write!(f, "couldn't compile ignore regex"),
+            Error::InvalidExtractionRegex(_) => write!(f, "couldn't compile extraction regex"),
+            Error::ZeroLengthStrategy(strategy) => write!(
+                f,
+                r#"strategy "{strategy}" would abbreviate components to nothing"#
+            ),
+            Error::ZeroMaxWidth => write!(f, "max width needs to be greater than 0"),
+        }
+    }
+}
+
+impl core::error::Error for Error {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        match self {
+            Error::InvalidDelimiterRegex(e)
+            | Error::InvalidIgnoreRegex(e)
+            | Error::InvalidExtractionRegex(e) => Some(e),
+            _ => None,
+        }
+    }
+}
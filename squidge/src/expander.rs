@@ -0,0 +1,209 @@
+use crate::{Config, shorten_line_into};
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+
+/// Maps shortened lines back to the originals they were produced from.
+///
+/// An `Expander` indexes a corpus of full lines by what they shorten to under a given
+/// [`Config`] (as written by [`shorten_line_into`]). Since shortening is lossy, several originals
+/// can shorten to the same string; [`Expander::expand`] reports all of them.
+///
+/// Example:
+/// ```
+/// use squidge::{Config, Expander, Expansion};
+///
+/// let corpus = [
+///     "src/main/scala/billing/Server.scala",
+///     "src/main/scala/backup/Server.scala",
+///     "src/main/scala/api/Server.scala",
+/// ];
+/// let expander = Expander::new(&Config::default(), corpus);
+///
+/// assert_eq!(
+///     expander.expand("s/m/s/a/Server.scala"),
+///     Expansion::Unique("src/main/scala/api/Server.scala")
+/// );
+/// assert_eq!(
+///     expander.expand("s/m/s/b/Server.scala"),
+///     Expansion::Ambiguous(vec![
+///         "src/main/scala/billing/Server.scala",
+///         "src/main/scala/backup/Server.scala",
+///     ])
+/// );
+/// assert_eq!(expander.expand("s/t/s/a/Server.scala"), Expansion::NotFound);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Expander {
+    originals: Vec<String>,
+    index: BTreeMap<String, Vec<usize>>,
+}
+
+/// The originals a shortened line expands to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Expansion<'e> {
+    /// No line in the corpus shortens to the given string
+    NotFound,
+    /// Exactly one line in the corpus shortens to the given string
+    Unique(&'e str),
+    /// Several lines in the corpus shorten to the given string (in corpus order)
+    Ambiguous(Vec<&'e str>),
+}
+
+impl Expander {
+    /// Indexes `lines` by what they shorten to under `cfg`. Duplicate lines are only indexed once.
+    pub fn new<I>(cfg: &Config, lines: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Into<String>,
+    {
+        let mut expander = Expander {
+            originals: vec![],
+            index: BTreeMap::new(),
+        };
+
+        for line in lines {
+            let line = line.into();
+            let mut shortened = String::with_capacity(line.len());
+            // writing to a String never fails
+            let _ = shorten_line_into(cfg, &line, &mut shortened);
+
+            let indices = expander.index.entry(shortened).or_default();
+            if indices.iter().any(|&i| expander.originals[i] == line) {
+                continue;
+            }
+            indices.push(expander.originals.len());
+            expander.originals.push(line);
+        }
+
+        expander
+    }
+
+    /// Returns every line in the corpus that shortens to `shortened`.
+    pub fn expand(&self, shortened: &str) -> Expansion<'_> {
+        match self.index.get(shortened).map(Vec::as_slice) {
+            None | Some([]) => Expansion::NotFound,
+            Some([i]) => Expansion::Unique(&self.originals[*i]),
+            Some(indices) => Expansion::Ambiguous(
+                indices
+                    .iter()
+                    .map(|&i| self.originals[i].as_str())
+                    .collect(),
+            ),
+        }
+    }
+
+    /// Number of distinct lines in the corpus.
+    pub fn len(&self) -> usize {
+        self.originals.len()
+    }
+
+    /// Whether the corpus is empty.
+    pub fn is_empty(&self) -> bool {
+        self.originals.is_empty()
+    }
+}
+
+impl<'e> Expansion<'e> {
+    /// All the originals, regardless of whether the expansion is ambiguous.
+    pub fn originals(&self) -> Vec<&'e str> {
+        match self {
+            Expansion::NotFound => vec![],
+            Expansion::Unique(original) => vec![original],
+            Expansion::Ambiguous(originals) => originals.clone(),
+        }
+    }
+
+    /// Whether more than one original shortens to the same string.
+    pub fn is_ambiguous(&self) -> bool {
+        matches!(self, Expansion::Ambiguous(_))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Delimiter, Strategy};
+    use alloc::string::ToString;
+    use insta::assert_yaml_snapshot;
+
+    const CORPUS: [&str; 5] = [
+        "src/main/scala/billing/Server.scala",
+        "src/main/scala/backup/Server.scala",
+        "src/test/scala/billing/Server.scala",
+        "src/main/scala/api/Server.scala",
+        "src/main/scala/api/Server.scala",
+    ];
+
+    #[test]
+    fn expanding_works_for_unique_ambiguous_and_unknown_lines() {
+        // GIVEN
+        let expander = Expander::new(&Config::default(), CORPUS);
+
+        // WHEN
+        let result: Vec<_> = [
+            "s/m/s/a/Server.scala",
+            "s/m/s/b/Server.scala",
+            "s/t/s/b/Server.scala",
+            "s/t/s/a/Server.scala",
+        ]
+        .into_iter()
+        .map(|l| expander.expand(l).originals())
+        .collect();
+
+        // THEN
+        assert_eq!(expander.len(), 4);
+        assert_yaml_snapshot!(result, @r"
+        - - src/main/scala/api/Server.scala
+        - - src/main/scala/billing/Server.scala
+          - src/main/scala/backup/Server.scala
+        - - src/test/scala/billing/Server.scala
+        - []
+        ");
+    }
+
+    #[test]
+    fn expanding_follows_config() {
+        // GIVEN
+        let cfg = Config {
+            delimiter: Delimiter::from(vec!["/", "::"]),
+            output_delimiter: " > ".to_string(),
+            ignore_first_n: 1,
+            strategy: Strategy::FirstChars(2),
+            ..Config::default()
+        };
+        let expander = Expander::new(&cfg, ["src/main::scala/billing/Server.scala"]);
+
+        // WHEN
+        let result = expander.expand("src > ma > sc > bi > Server.scala");
+
+        // THEN
+        assert_eq!(
+            result,
+            Expansion::Unique("src/main::scala/billing/Server.scala")
+        );
+        assert!(!result.is_ambiguous());
+        assert_eq!(expander.expand("s/m/s/b/Server.scala"), Expansion::NotFound);
+    }
+
+    #[test]
+    fn expanding_works_with_kept_delimiters() {
+        // GIVEN
+        let cfg = Config {
+            delimiter: Delimiter::from(vec!["/", "."]),
+            keep_delimiters: true,
+            ..Config::default()
+        };
+        let expander = Expander::new(
+            &cfg,
+            ["com.example/service/Handler", "com/example.service/Handler"],
+        );
+
+        // WHEN
+        let result = expander.expand("c.e/s/Handler");
+
+        // THEN
+        assert_eq!(result, Expansion::Unique("com.example/service/Handler"));
+    }
+}
@@ -0,0 +1,81 @@
+use crate::Regex;
+use alloc::borrow::Cow;
+use alloc::string::String;
+
+/// Abbreviates components by extracting parts of them via a regex's capture groups.
+///
+/// Components that are to be shortened and that match `regex` are replaced by `replacement`, with
+/// references to capture groups (`$1`, `${name}`, etc.) expanded, as is done by `regex`'s
+/// `Captures::expand`; the parts of the component that aren't captured are left out.
+/// Components that don't match are abbreviated using [`Config::strategy`](crate::Config::strategy).
+///
+/// Example:
+/// ```
+/// use squidge::{Config, Extraction, Regex, shorten_line};
+///
+/// let cfg = Config {
+///     extraction: Some(Extraction {
+///         regex: Regex::new(r"service-(\w)\w*-(v\d+)").unwrap(),
+///         replacement: "$1$2".to_string(),
+///     }),
+///     ..Config::default()
+/// };
+/// let result = shorten_line(&cfg, "services/service-billing-v2/api/lib.rs");
+/// assert_eq!(result, vec!["s", "bv2", "a", "lib.rs"]);
+/// ```
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Extraction {
+    /// Regex with capture groups to match components against
+    #[cfg_attr(
+        feature = "serde",
+        serde(with = "crate::serialization::required_regex")
+    )]
+    pub regex: Regex,
+    /// Template that matching components are replaced with
+    pub replacement: String,
+}
+
+impl Extraction {
+    /// Returns the expanded replacement if `component` matches the regex.
+    pub(crate) fn extract<'c>(&self, component: &'c str) -> Option<Cow<'c, str>> {
+        self.regex
+            .expand(component, &self.replacement)
+            .map(Cow::Owned)
+    }
+}
+
+/// Extractions with regexes are compared by their patterns and engines.
+impl PartialEq for Extraction {
+    fn eq(&self, other: &Self) -> bool {
+        self.regex.key() == other.regex.key() && self.replacement == other.replacement
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::string::ToString;
+    use alloc::vec::Vec;
+    use insta::assert_yaml_snapshot;
+
+    #[test]
+    fn extract_expands_capture_groups() {
+        // GIVEN
+        let extraction = Extraction {
+            regex: Regex::new(r"^(?<year>\d{4})-\d{2}-\d{2}-(\w)\w*").unwrap(),
+            replacement: "${year}$2".to_string(),
+        };
+        let components = ["2024-03-17-migration", "2024-03-17", "migration"];
+
+        // WHEN
+        let result: Vec<_> = components.iter().map(|c| extraction.extract(c)).collect();
+
+        // THEN
+        assert_yaml_snapshot!(result, @r"
+        - 2024m
+        - ~
+        - ~
+        ");
+    }
+}
@@ -0,0 +1,106 @@
+use alloc::borrow::Cow;
+use alloc::format;
+
+/// Extensions made up of several parts, which are kept as a whole.
+const COMPOUND_EXTENSIONS: [&str; 9] = [
+    ".tar.gz", ".tar.bz2", ".tar.xz", ".tar.zst", ".tar.lz", ".d.ts", ".d.mts", ".d.cts", ".d.tsx",
+];
+
+/// Splits a file name into its leading dot (if any), its stem, and its extension (if any).
+///
+/// The extension is everything from the last dot onwards, unless the name ends with one of
+/// [`COMPOUND_EXTENSIONS`]. The stem is never empty; names that consist of nothing but dots and
+/// an extension (eg, `.gz`) are treated as a dot followed by a stem.
+pub(crate) fn split(name: &str) -> (&str, &str, &str) {
+    let (dot, rest) = match name.strip_prefix('.') {
+        Some(rest) if !rest.is_empty() => (".", rest),
+        _ => ("", name),
+    };
+
+    let extension_start = COMPOUND_EXTENSIONS
+        .iter()
+        .find(|extension| rest.len() > extension.len() && rest.ends_with(*extension))
+        .map(|extension| rest.len() - extension.len())
+        .or_else(|| rest.rfind('.').filter(|&i| i > 0 && i < rest.len() - 1));
+
+    match extension_start {
+        Some(i) => (dot, &rest[..i], &rest[i..]),
+        None => (dot, rest, ""),
+    }
+}
+
+/// Abbreviates the stem of a file name via `abbreviate`, keeping its leading dot and extension.
+pub(crate) fn abbreviate<'c, F>(name: &'c str, abbreviate: F) -> Cow<'c, str>
+where
+    F: FnOnce(&'c str) -> Cow<'c, str>,
+{
+    match split(name) {
+        ("", stem, "") => abbreviate(stem),
+        (dot, stem, extension) => Cow::Owned(format!("{dot}{}{extension}", abbreviate(stem))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec::Vec;
+    use insta::assert_yaml_snapshot;
+
+    #[test]
+    fn split_works() {
+        // GIVEN
+        let names = [
+            "ApplicationComponents.scala",
+            "archive.tar.gz",
+            "index.d.ts",
+            "jquery.min.js",
+            ".config",
+            ".eslintrc.json",
+            "Makefile",
+            "file.",
+            ".",
+            "..",
+            ".tar.gz",
+        ];
+
+        // WHEN
+        let result: Vec<_> = names.iter().map(|n| split(n)).collect();
+
+        // THEN
+        assert_yaml_snapshot!(result, @r#"
+        - - ""
+          - ApplicationComponents
+          - ".scala"
+        - - ""
+          - archive
+          - ".tar.gz"
+        - - ""
+          - index
+          - ".d.ts"
+        - - ""
+          - jquery.min
+          - ".js"
+        - - "."
+          - config
+          - ""
+        - - "."
+          - eslintrc
+          - ".json"
+        - - ""
+          - Makefile
+          - ""
+        - - ""
+          - file.
+          - ""
+        - - ""
+          - "."
+          - ""
+        - - "."
+          - "."
+          - ""
+        - - "."
+          - tar
+          - ".gz"
+        "#);
+    }
+}
@@ -11,44 +11,476 @@ let result = shorten_line(&Config::default(), &line);
 let expected = vec!["m", "s", "s", "lib.rs"];
 assert_eq!(result, expected);
 ```
+
+With the (default) `std` feature disabled, the crate is `no_std`, and only needs `alloc`; regexes
+are then compiled via the `regex-automata` or `fancy-regex` engines (see [`Engine`]).
 */
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+mod abbreviator;
+mod alias;
+mod builder;
+mod bytes;
+mod collapse;
+mod common_prefix;
+mod delimiter;
+mod elision;
+mod empty;
+mod error;
+mod expander;
+mod extraction;
+mod filename;
+#[cfg(feature = "std")]
+mod path;
+mod prefix;
+mod quoting;
+mod regex;
+mod rule;
+mod segment;
+mod selector;
+#[cfg(feature = "serde")]
+mod serialization;
+mod tree;
+mod unique;
+mod units;
+mod width;
+
+pub use crate::regex::{Engine, ParseEngineError, Regex, RegexError};
+pub use abbreviator::{Abbreviator, ParseStrategyError, Strategy};
+pub use alias::Alias;
+pub use builder::ConfigBuilder;
+pub use bytes::{escape_bytes, shorten_bytes, unescape_bytes};
+pub use collapse::{Collapse, ParseCollapseError};
+pub use common_prefix::{CommonPrefix, split_common_prefix};
+pub use delimiter::Delimiter;
+pub use elision::Elision;
+pub use empty::{EmptyComponents, ParseEmptyComponentsError};
+pub use error::Error;
+pub use expander::{Expander, Expansion};
+pub use extraction::Extraction;
+#[cfg(feature = "std")]
+pub use path::PathExt;
+pub use prefix::Prefix;
+pub use quoting::Quoting;
+pub use rule::{Action, Matcher, ParseRuleError, Rule};
+pub use segment::{Reason, Segment, ShortenedLine};
+pub use selector::{ParseSelectorError, Selector};
+pub use tree::{Node, Tree};
+pub use unique::shorten_lines_unique;
 
-use regex::Regex;
+use alias::{Aliased, Part};
+use alloc::borrow::Cow;
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+use collapse::Repeat;
+use core::fmt;
+use delimiter::Split;
+use elision::Elided;
+use rule::Decision;
+use width::Measure;
 
 /// Represents the config used by squidge.
 ///
+/// A `Config` can be created directly, as shown below, or via [`Config::builder`], which
+/// validates its values.
+///
 /// Example usage:
 /// ```
-/// use squidge::Config;
-/// use regex::Regex;
+/// use squidge::{
+///     Alias, Collapse, Config, Delimiter, Elision, EmptyComponents, Prefix, Quoting, Regex,
+///     Strategy,
+/// };
 ///
 /// let re = Regex::new("module").unwrap();
 /// let cfg = Config {
-///     delimiter: "\\",
+///     delimiter: Delimiter::from("\\"),
+///     output_delimiter: "/".to_string(),
+///     keep_delimiters: false,
+///     quoting: Some(Quoting::default()),
 ///     ignore_first_n: 2,
 ///     ignore_last_n: 2,
 ///     ignore_regex: Some(re),
+///     ignore_indices: Some("2,-2:".parse().unwrap()),
+///     empty_components: EmptyComponents::Drop,
+///     absolute_root: true,
+///     rules: vec!["word:src => drop".parse().unwrap()],
+///     prefixes: vec![Prefix::new("/home/alice", "~")],
+///     aliases: vec![Alias::new(["src", "main", "scala"], "main")],
+///     extraction: None,
+///     strategy: Strategy::FirstChars(2),
+///     filename_aware: false,
+///     collapse: Collapse::Runs,
+///     elision: Some(Elision::default()),
+///     max_width: Some(40),
 /// };
 /// ```
-#[derive(Debug)]
-pub struct Config<'a> {
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+// deserialization is done via the derived impl, and validated on top of it (see serialization.rs)
+#[cfg_attr(feature = "serde", serde(default, remote = "Self"))]
+pub struct Config {
     /// Delimiter to split the line on
-    pub delimiter: &'a str,
+    pub delimiter: Delimiter,
+    /// Delimiter to join the shortened components with (used by [`shorten_line_into`])
+    pub output_delimiter: String,
+    /// Whether to join the shortened components with the delimiters they were originally
+    /// separated by, instead of `output_delimiter`
+    pub keep_delimiters: bool,
+    /// Optional quoting mode, in which delimiters within quoted components don't split them, and
+    /// output components containing the delimiter they're joined with are quoted
+    pub quoting: Option<Quoting>,
     /// Number of elements to ignore (for shortening) from the start
     pub ignore_first_n: usize,
     /// Number of elements to ignore (for shortening) from the end
     pub ignore_last_n: usize,
     /// Optional regex to determine which components to ignore while shortening
+    #[cfg_attr(feature = "serde", serde(with = "serialization::optional_regex"))]
     pub ignore_regex: Option<Regex>,
+    /// Optional selector (eg, `2,-2:`) for the indices of components to ignore while shortening,
+    /// on top of the first and last few
+    pub ignore_indices: Option<Selector>,
+    /// What happens to empty components (`module//service`, or the ones before leading and after
+    /// trailing delimiters)
+    pub empty_components: EmptyComponents,
+    /// Whether to preserve the leading delimiter of absolute lines (`/usr/local/bin`) as is,
+    /// without it counting as a component (towards `ignore_first_n`, positions in rules, etc.)
+    pub absolute_root: bool,
+    /// Ordered rules that determine what happens to the components they match; the first
+    /// matching rule wins, and takes precedence over the settings above
+    pub rules: Vec<Rule>,
+    /// Known prefixes of lines (eg, the home directory) to replace with symbolic tokens before
+    /// anything else happens; tokens count as single components, and are kept as is
+    pub prefixes: Vec<Prefix>,
+    /// Sequences of components to replace with single tokens before shortening; tokens count as
+    /// single components, and are kept as is
+    pub aliases: Vec<Alias>,
+    /// Optional regex (with capture groups) and replacement template that components to be
+    /// shortened are abbreviated with, if they match; others are abbreviated using `strategy`
+    pub extraction: Option<Extraction>,
+    /// Strategy used to abbreviate the components that are to be shortened
+    pub strategy: Strategy,
+    /// Whether to treat components as file names while abbreviating them, ie, keep their
+    /// extensions (including compound ones, like `.tar.gz`) and leading dots as is, and only
+    /// abbreviate the rest (`ApplicationComponents.scala` -> `A.scala`, `.config` -> `.c`)
+    pub filename_aware: bool,
+    /// Whether to collapse repeated components (or blocks of them) into a single annotated token
+    /// (`build/build/build` -> `b×3`)
+    pub collapse: Collapse,
+    /// Optional number of components to keep from the start and end of lines, the ones in between
+    /// being replaced with a marker (`src/main/scala/admin/Server.scala` -> `s/…/Server.scala`)
+    pub elision: Option<Elision>,
+    /// Optional width budget (in terminal columns) for the line joined by `output_delimiter`
+    /// (quotes included); when set, components are only abbreviated (starting from the first one)
+    /// until the line fits
+    pub max_width: Option<usize>,
+}
+
+impl Config {
+    /// Returns a builder that validates the config's values, and compiles regexes from strings.
+    ///
+    /// Example:
+    /// ```
+    /// use squidge::Config;
+    ///
+    /// let cfg = Config::builder()
+    ///     .delimiter("\\")
+    ///     .ignore_first_n(2)
+    ///     .ignore_last_n(2)
+    ///     .ignore_regex("module")
+    ///     .build()
+    ///     .unwrap();
+    /// ```
+    pub fn builder() -> ConfigBuilder {
+        ConfigBuilder::default()
+    }
+
+    /// Splits a line into its parts, after substituting its prefix, setting aside its root (if
+    /// preserved), leaving out empty components (if they're dropped), and applying aliases.
+    pub(crate) fn parts<'a, 'l>(&'a self, line: &'l str) -> Aliased<'a, 'l> {
+        let drop_empty = |split: Split<'a, 'l>| match self.empty_components {
+            EmptyComponents::Drop => split.skip_empty(),
+            _ => split,
+        };
+
+        if let Some((token, source, delimiter, rest)) =
+            prefix::substitute(&self.prefixes, &self.delimiter, line)
+        {
+            let rest = rest.quoted(self.quoting.as_ref());
+            return Aliased::new(&self.aliases, drop_empty(rest)).after(token, delimiter, source);
+        }
+
+        let mut split = self.split(line);
+        let root = match split.clone().next() {
+            Some((component, Some(root))) if component.is_empty() && self.absolute_root => {
+                split.next();
+                Some(root)
+            }
+            _ => None,
+        };
+
+        Aliased::new(&self.aliases, drop_empty(split)).with_root(root)
+    }
+
+    /// Splits a line into its components, respecting quotes if configured to.
+    pub(crate) fn split<'a, 'l>(&'a self, line: &'l str) -> Split<'a, 'l> {
+        self.delimiter.split(line).quoted(self.quoting.as_ref())
+    }
+
+    /// Quotes an output component if configured to, and if it needs quoting.
+    pub(crate) fn quote<'c>(&self, component: Cow<'c, str>) -> Cow<'c, str> {
+        let Some(quoting) = &self.quoting else {
+            return component;
+        };
+
+        quoting.quote(component, |component| match self.joining_delimiter() {
+            Some(delimiter) => !delimiter.is_empty() && component.contains(delimiter),
+            None => self.delimiter.matches_in(component),
+        })
+    }
+
+    /// The delimiter to join components with; `None` means the original delimiters are to be
+    /// used.
+    pub(crate) fn joining_delimiter(&self) -> Option<&str> {
+        (!self.keep_delimiters).then_some(self.output_delimiter.as_str())
+    }
+
+    /// Determines what is to be done to the component at index `i` (in a line with
+    /// `num_elements` delimiters).
+    pub(crate) fn decide(&self, i: usize, num_elements: usize, component: &str) -> Decision<'_> {
+        self.judge(i, num_elements, component).0
+    }
+
+    /// Determines what is to be done to the component at index `i` (in a line with
+    /// `num_elements` delimiters), along with the reason for it.
+    fn judge(&self, i: usize, num_elements: usize, component: &str) -> (Decision<'_>, Reason) {
+        if component.is_empty()
+            && let EmptyComponents::Placeholder(placeholder) = &self.empty_components
+        {
+            return (Decision::Alias(placeholder), Reason::Empty);
+        }
+
+        let rule = self
+            .rules
+            .iter()
+            .position(|rule| rule.matcher.matches(i, num_elements + 1, component));
+        if let Some(index) = rule {
+            return (self.rules[index].action.decision(), Reason::Rule(index));
+        }
+
+        let ignored = self.ignore_reason(i, num_elements, component);
+        let decision = match ignored {
+            Some(_) => Decision::Keep,
+            None => Decision::Shorten,
+        };
+        let reason = match ignored {
+            _ if component.is_empty() => Reason::Empty,
+            Some(reason) => reason,
+            None => Reason::Shortened,
+        };
+
+        (decision, reason)
+    }
+
+    /// Abbreviates a component that is to be shortened as per the config; `abbreviation`, if
+    /// provided, is used instead of the one the strategy would produce.
+    pub(crate) fn abbreviate<'c>(
+        &self,
+        component: &'c str,
+        abbreviation: Option<Cow<'c, str>>,
+    ) -> Cow<'c, str> {
+        self.extraction
+            .as_ref()
+            .and_then(|extraction| extraction.extract(component))
+            .or(abbreviation)
+            .unwrap_or_else(|| self.abbreviate_with(&self.strategy, component))
+    }
+
+    /// Abbreviates a component using `strategy`, keeping its extension and leading dot if the
+    /// config is filename aware.
+    pub(crate) fn abbreviate_with<'c>(
+        &self,
+        strategy: &Strategy,
+        component: &'c str,
+    ) -> Cow<'c, str> {
+        if self.filename_aware {
+            filename::abbreviate(component, |stem| strategy.abbreviate(stem))
+        } else {
+            strategy.abbreviate(component)
+        }
+    }
+
+    /// Shortens the part at index `i` (in a line with `num_elements` delimiters) as per the
+    /// config, only abbreviating it if `abbreviate` is set, and returns it along with the reason
+    /// for its output; `None` means it's dropped. `abbreviation` stands in for the strategy's
+    /// abbreviation of the part (see [`Config::abbreviate`]).
+    pub(crate) fn shorten_part<'l>(
+        &self,
+        i: usize,
+        num_elements: usize,
+        part: Part<'_, 'l>,
+        abbreviate: bool,
+        abbreviation: Option<Cow<'l, str>>,
+    ) -> Option<(Cow<'l, str>, Reason)> {
+        let component = match part {
+            Part::Alias(token) => return Some((Cow::Owned(token.to_string()), Reason::Alias)),
+            Part::Component(component) => component,
+        };
+
+        let (decision, reason) = self.judge(i, num_elements, &component);
+        let shortened = match decision {
+            Decision::Drop => return None,
+            Decision::Alias(alias) => Cow::Owned(alias.to_string()),
+            Decision::Keep => component,
+            _ if !abbreviate => {
+                let reason = match reason {
+                    Reason::Shortened => Reason::FitsMaxWidth,
+                    reason => reason,
+                };
+                return Some((component, reason));
+            }
+            // unescaped components aren't borrowed from the line, and so can't be abbreviated
+            // into slices of it
+            decision => match component {
+                Cow::Borrowed(component) => self.abbreviated(decision, component, abbreviation),
+                Cow::Owned(component) => Cow::Owned(
+                    self.abbreviated(decision, &component, abbreviation)
+                        .into_owned(),
+                ),
+            },
+        };
+
+        Some((shortened, reason))
+    }
+
+    /// Abbreviates a component that is to be shortened or abbreviated as per `decision`;
+    /// `abbreviation` is as for [`Config::shorten_part`].
+    fn abbreviated<'c>(
+        &self,
+        decision: Decision<'_>,
+        component: &'c str,
+        abbreviation: Option<Cow<'c, str>>,
+    ) -> Cow<'c, str> {
+        match decision {
+            Decision::Abbreviate(strategy) => self.abbreviate_with(strategy, component),
+            _ => self.abbreviate(component, abbreviation),
+        }
+    }
+
+    /// Measures the component at index `i` (in a line with `num_elements` delimiters) as per the
+    /// config; `abbreviation` is as for [`Config::shorten_part`].
+    fn measure(
+        &self,
+        i: usize,
+        num_elements: usize,
+        component: &str,
+        abbreviation: Option<&str>,
+    ) -> Measure {
+        match self.decide(i, num_elements, component) {
+            Decision::Keep => self.measure_output(component, None),
+            Decision::Alias(alias) => self.measure_output(alias, None),
+            Decision::Drop => Measure::Dropped,
+            decision => self.measure_output(
+                component,
+                Some(&self.abbreviated(decision, component, abbreviation.map(Cow::Borrowed))),
+            ),
+        }
+    }
+
+    /// Measures a component (possibly abbreviated to `abbreviated`) as it's output, ie, quoted if
+    /// it needs to be.
+    fn measure_output(&self, component: &str, abbreviated: Option<&str>) -> Measure {
+        let abbreviated = abbreviated.map(|abbreviated| self.quote(Cow::Borrowed(abbreviated)));
+        Measure::of(
+            &self.quote(Cow::Borrowed(component)),
+            abbreviated.as_deref(),
+        )
+    }
+
+    /// Whether the part at index `i` (in a line with `num_elements` delimiters) is output, ie,
+    /// not dropped.
+    fn keeps(&self, i: usize, num_elements: usize, part: &Part<'_, '_>) -> bool {
+        match part {
+            Part::Alias(_) => true,
+            Part::Component(component) => {
+                !matches!(self.decide(i, num_elements, component), Decision::Drop)
+            }
+        }
+    }
+
+    /// Why a component that no rule matches is to be kept as is; `None` means it's to be
+    /// shortened.
+    fn ignore_reason(&self, i: usize, num_elements: usize, component: &str) -> Option<Reason> {
+        if i < self.ignore_first_n {
+            return Some(Reason::IgnoredFirstN);
+        }
+        if self.ignore_last_n > num_elements || i > num_elements - self.ignore_last_n {
+            return Some(Reason::IgnoredLastN);
+        }
+        if self
+            .ignore_indices
+            .as_ref()
+            .is_some_and(|selector| selector.contains(i, num_elements + 1))
+        {
+            return Some(Reason::IgnoredIndex);
+        }
+
+        self.ignore_regex
+            .as_ref()
+            .is_some_and(|r| r.is_match(component))
+            .then_some(Reason::MatchedIgnoreRegex)
+    }
+}
+
+impl PartialEq for Config {
+    fn eq(&self, other: &Self) -> bool {
+        self.delimiter == other.delimiter
+            && self.output_delimiter == other.output_delimiter
+            && self.keep_delimiters == other.keep_delimiters
+            && self.quoting == other.quoting
+            && self.ignore_first_n == other.ignore_first_n
+            && self.ignore_last_n == other.ignore_last_n
+            && self.ignore_regex.as_ref().map(Regex::key)
+                == other.ignore_regex.as_ref().map(Regex::key)
+            && self.ignore_indices == other.ignore_indices
+            && self.empty_components == other.empty_components
+            && self.absolute_root == other.absolute_root
+            && self.rules == other.rules
+            && self.prefixes == other.prefixes
+            && self.aliases == other.aliases
+            && self.extraction == other.extraction
+            && self.strategy == other.strategy
+            && self.filename_aware == other.filename_aware
+            && self.collapse == other.collapse
+            && self.elision == other.elision
+            && self.max_width == other.max_width
+    }
 }
 
-impl Default for Config<'_> {
+impl Default for Config {
     fn default() -> Self {
         Config {
-            delimiter: "/",
+            delimiter: Delimiter::from("/"),
+            output_delimiter: "/".to_string(),
+            keep_delimiters: false,
+            quoting: None,
             ignore_first_n: 0,
             ignore_last_n: 1,
             ignore_regex: None,
+            ignore_indices: None,
+            empty_components: EmptyComponents::Keep,
+            absolute_root: false,
+            rules: vec![],
+            prefixes: vec![],
+            aliases: vec![],
+            extraction: None,
+            strategy: Strategy::default(),
+            filename_aware: false,
+            collapse: Collapse::Off,
+            elision: None,
+            max_width: None,
         }
     }
 }
@@ -57,8 +489,7 @@ impl Default for Config<'_> {
 ///
 /// Example:
 /// ```
-/// use regex::Regex;
-/// use squidge::{Config, shorten_line};
+/// use squidge::{Config, Regex, shorten_line};
 ///
 /// let line = "/path/to/a/module/submodule/service/lib.rs";
 /// let re = Regex::new("module").unwrap();
@@ -83,43 +514,392 @@ impl Default for Config<'_> {
 /// ```
 ///
 pub fn shorten_line(cfg: &Config, line: &str) -> Vec<String> {
-    let num_elements = line.matches(cfg.delimiter).count();
-    let line_iter = line.split(cfg.delimiter);
-    let mut shortened_elements: Vec<String> = Vec::new();
+    shorten_components(cfg, line).map(Cow::into_owned).collect()
+}
 
-    for (i, component) in line_iter.enumerate() {
-        if i < cfg.ignore_first_n
-            || (cfg.ignore_last_n > num_elements || i > num_elements - cfg.ignore_last_n)
-        {
-            shortened_elements.push(component.to_string());
-            continue;
-        }
+/// Lazily shortens a line based on the provided configuration.
+///
+/// Components that are kept as is, as well as the ones whose abbreviation is a slice of the
+/// original (as is the case for [`Strategy::FirstChars`] and [`Strategy::LastChars`]), are
+/// borrowed from `line`; nothing is allocated for them.
+///
+/// Example:
+/// ```
+/// use squidge::{Config, shorten_components};
+/// use std::borrow::Cow;
+///
+/// let cfg = Config::default();
+/// let line = "module/submodule/service/lib.rs";
+/// let mut components = shorten_components(&cfg, line);
+/// assert_eq!(components.next(), Some(Cow::Borrowed("m")));
+/// assert_eq!(components.last(), Some(Cow::Borrowed("lib.rs")));
+/// ```
+pub fn shorten_components<'a, 'l>(cfg: &'a Config, line: &'l str) -> ShortenedComponents<'a, 'l> {
+    shorten_components_with(cfg, line, Vec::new())
+}
 
-        let shorten_element = match cfg.ignore_regex {
-            Some(ref r) => match r.is_match(component) {
-                true => false,
-                false => true,
+/// Like [`shorten_components`], with `abbreviations[i]` (if there is one) standing in for the
+/// strategy's abbreviation of the part at index `i`.
+pub(crate) fn shorten_components_with<'a, 'l>(
+    cfg: &'a Config,
+    line: &'l str,
+    abbreviations: Vec<Option<Cow<'l, str>>>,
+) -> ShortenedComponents<'a, 'l> {
+    let parts = cfg.parts(line);
+    let num_parts = parts.clone().count();
+    let num_elements = num_parts.saturating_sub(1);
+    let root = parts.root();
+    let elided = cfg.elision.as_ref().and_then(|elision| {
+        elision.elide(num_parts, |range| {
+            parts
+                .clone()
+                .enumerate()
+                .take(range.end)
+                .skip(range.start)
+                .filter(|(i, (part, _))| cfg.keeps(*i, num_elements, part))
+                .count()
+        })
+    });
+    let repeats = match cfg.collapse {
+        Collapse::Off => vec![],
+        collapse => collapse.repeats(&parts.clone().map(|(part, _)| part).collect::<Vec<_>>()),
+    };
+    // repeats are only collapsed outside of the elided parts
+    let repeats: Vec<_> = repeats
+        .into_iter()
+        .filter(|repeat| {
+            !elided
+                .as_ref()
+                .is_some_and(|elided| elided.overlaps(repeat))
+        })
+        .collect();
+    let shorten_before = match cfg.max_width {
+        Some(max_width) => width::width_cutoff(
+            // the root is always output
+            max_width.saturating_sub(root.map_or(0, |root| {
+                width::display_width(cfg.joining_delimiter().unwrap_or(root))
+            })),
+            cfg.joining_delimiter(),
+            parts.clone(),
+            |i, part| {
+                let measure = match part {
+                    Part::Alias(token) => cfg.measure_output(token, None),
+                    Part::Component(component) => cfg.measure(
+                        i,
+                        num_elements,
+                        &component,
+                        abbreviations.get(i).and_then(Option::as_deref),
+                    ),
+                };
+                let measure = match repeats.iter().find(|r| (r.start..r.end()).contains(&i)) {
+                    Some(repeat) => repeat.measure(i, measure),
+                    None => measure,
+                };
+                match elided {
+                    Some(ref elided) => elided.measure(i, measure),
+                    None => measure,
+                }
             },
-            None => true,
-        };
+        ),
+        None => usize::MAX,
+    };
 
-        let shortened_element = match shorten_element {
-            true => match component.chars().next() {
-                Some(c) => c.to_string(),
-                None => String::new(),
-            },
-            false => component.to_string(),
+    ShortenedComponents {
+        cfg,
+        line,
+        prefixed: parts.substitutes_prefix(),
+        num_parts,
+        num_elements,
+        parts,
+        root,
+        index: 0,
+        shorten_before,
+        repeats: repeats.into_iter().rev().collect(),
+        elided,
+        abbreviations,
+        peeked: None,
+    }
+}
+
+/// Shortens a line based on the provided configuration and returns each component together with
+/// the delimiter that followed it in `line` (the last component has none).
+///
+/// Example:
+/// ```
+/// use squidge::{Config, Delimiter, shorten_line_with_delimiters};
+///
+/// let cfg = Config {
+///     delimiter: Delimiter::from(vec!["/", "::"]),
+///     ..Config::default()
+/// };
+/// let result = shorten_line_with_delimiters(&cfg, "module/service::lib.rs");
+/// let expected = vec![
+///     ("m".to_string(), Some("/".to_string())),
+///     ("s".to_string(), Some("::".to_string())),
+///     ("lib.rs".to_string(), None),
+/// ];
+/// assert_eq!(result, expected);
+/// ```
+pub fn shorten_line_with_delimiters(cfg: &Config, line: &str) -> Vec<(String, Option<String>)> {
+    shorten_components(cfg, line)
+        .with_delimiters()
+        .map(|(component, delimiter)| (component.into_owned(), delimiter.map(String::from)))
+        .collect()
+}
+
+/// Shortens a line based on the provided configuration, and writes the components, joined by
+/// [`Config::output_delimiter`] (or the original delimiters, if [`Config::keep_delimiters`] is
+/// set), to `out`.
+///
+/// Example:
+/// ```
+/// use squidge::{Config, shorten_line_into};
+///
+/// let cfg = Config {
+///     output_delimiter: "::".to_string(),
+///     ..Config::default()
+/// };
+/// let mut buffer = String::new();
+/// shorten_line_into(&cfg, "module/submodule/service/lib.rs", &mut buffer).unwrap();
+/// assert_eq!(buffer, "m::s::s::lib.rs");
+/// ```
+pub fn shorten_line_into<W: fmt::Write>(cfg: &Config, line: &str, out: &mut W) -> fmt::Result {
+    let joining_delimiter = cfg.joining_delimiter();
+    for (component, delimiter) in shorten_components(cfg, line).with_delimiters() {
+        out.write_str(&component)?;
+        if let Some(delimiter) = delimiter {
+            out.write_str(joining_delimiter.unwrap_or(delimiter))?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Iterator over the shortened components of a line.
+///
+/// Returned by [`shorten_components`].
+#[derive(Debug)]
+pub struct ShortenedComponents<'a, 'l> {
+    cfg: &'a Config,
+    line: &'l str,
+    prefixed: bool,
+    parts: Aliased<'a, 'l>,
+    num_parts: usize,
+    num_elements: usize,
+    root: Option<&'l str>,
+    index: usize,
+    shorten_before: usize,
+    // in reverse order, so the next one can be popped
+    repeats: Vec<Repeat>,
+    elided: Option<Elided>,
+    abbreviations: Vec<Option<Cow<'l, str>>>,
+    peeked: Option<Segment<'l>>,
+}
+
+impl<'a, 'l> ShortenedComponents<'a, 'l> {
+    /// Pairs each shortened component with the delimiter that followed it in the line.
+    pub fn with_delimiters(self) -> WithDelimiters<'a, 'l> {
+        WithDelimiters { inner: self }
+    }
+
+    /// Consumes the iterator, and returns the segments of the line.
+    pub(crate) fn segments(mut self) -> impl Iterator<Item = Segment<'l>> {
+        core::iter::from_fn(move || self.next_segment())
+    }
+
+    /// Returns the next segment that isn't dropped; its delimiter is left out if every segment
+    /// after it is dropped.
+    fn next_segment(&mut self) -> Option<Segment<'l>> {
+        // the root is followed by its delimiter even if nothing else is
+        if let Some(root) = self.root.take() {
+            return Some(self.segment(
+                &self.line[..0],
+                Cow::Borrowed(""),
+                Some(root),
+                Reason::Root,
+            ));
+        }
+
+        let mut segment = match self.peeked.take() {
+            Some(peeked) => peeked,
+            None => self.next_kept()?,
         };
 
-        shortened_elements.push(shortened_element);
+        if segment.delimiter.is_none() {
+            return Some(segment);
+        }
+
+        self.peeked = self.next_kept();
+        if self.peeked.is_none() {
+            segment.delimiter = None;
+        }
+
+        Some(segment)
+    }
+
+    fn next_kept(&mut self) -> Option<Segment<'l>> {
+        loop {
+            if let Some(elided) = self.elided.take_if(|e| e.range.start == self.index) {
+                return self.next_elided(elided);
+            }
+
+            if self.repeats.last().is_some_and(|r| r.start == self.index) {
+                let repeat = self.repeats.pop()?;
+                match self.next_collapsed(&repeat) {
+                    Some(collapsed) => return Some(collapsed),
+                    None => continue,
+                }
+            }
+
+            let (part, delimiter, source) = self.parts.next_with_source()?;
+            let i = self.index;
+            self.index += 1;
+
+            let abbreviate = i < self.shorten_before;
+            let abbreviation = self.abbreviations.get_mut(i).and_then(Option::take);
+            if let Some((shortened, reason)) =
+                self.cfg
+                    .shorten_part(i, self.num_elements, part, abbreviate, abbreviation)
+            {
+                let reason = match reason {
+                    Reason::Alias if i == 0 && self.prefixed => Reason::Prefix,
+                    reason => reason,
+                };
+                let shortened = self.cfg.quote(shortened);
+                return Some(self.segment(source, shortened, delimiter, reason));
+            }
+        }
+    }
+
+    /// Consumes every repetition of a block, and returns its first occurrence collapsed into a
+    /// single token, along with the delimiter that followed the last repetition; `None` if every
+    /// part of the block is dropped.
+    fn next_collapsed(&mut self, repeat: &Repeat) -> Option<Segment<'l>> {
+        let mut block = Vec::with_capacity(repeat.len);
+        let mut sources = None;
+        let mut last_delimiter = None;
+        while self.index < repeat.end() {
+            let (part, delimiter, source) = self.parts.next_with_source()?;
+            let i = self.index;
+            self.index += 1;
+            last_delimiter = delimiter;
+            sources = Some((sources.map_or(source, |(first, _)| first), source));
+
+            if repeat.is_first_occurrence(i) {
+                let abbreviate = i < self.shorten_before;
+                let abbreviation = self.abbreviations.get_mut(i).and_then(Option::take);
+                if let Some((shortened, _)) =
+                    self.cfg
+                        .shorten_part(i, self.num_elements, part, abbreviate, abbreviation)
+                {
+                    block.push((self.cfg.quote(shortened), delimiter));
+                }
+            }
+        }
+
+        let (first, last) = sources?;
+        collapse::collapse(repeat, &block, self.cfg.joining_delimiter()).map(|collapsed| {
+            self.segment(
+                delimiter::between(self.line, first, last),
+                Cow::Owned(collapsed),
+                last_delimiter,
+                Reason::Collapsed,
+            )
+        })
+    }
+
+    /// Consumes the elided parts, and returns the marker they're replaced with, along with the
+    /// delimiter that followed the last of them.
+    fn next_elided(&mut self, elided: Elided) -> Option<Segment<'l>> {
+        let mut sources = None;
+        let mut last_delimiter = None;
+        while self.index < elided.range.end {
+            let (_, delimiter, source) = self.parts.next_with_source()?;
+            self.index += 1;
+            last_delimiter = delimiter;
+            sources = Some((sources.map_or(source, |(first, _)| first), source));
+        }
+
+        let (first, last) = sources?;
+        Some(self.segment(
+            delimiter::between(self.line, first, last),
+            Cow::Owned(elided.marker),
+            last_delimiter,
+            Reason::Elided,
+        ))
+    }
+
+    fn segment(
+        &self,
+        original: &'l str,
+        output: Cow<'l, str>,
+        delimiter: Option<&'l str>,
+        reason: Reason,
+    ) -> Segment<'l> {
+        let start = delimiter::offset(self.line, original);
+        Segment {
+            original,
+            output,
+            span: start..start + original.len(),
+            delimiter,
+            reason,
+        }
+    }
+}
+
+impl<'l> Iterator for ShortenedComponents<'_, 'l> {
+    type Item = Cow<'l, str>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_segment().map(|segment| segment.output)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let peeked = usize::from(self.peeked.is_some()) + usize::from(self.root.is_some());
+        let remaining = self.num_parts.saturating_sub(self.index) + peeked;
+        let may_drop = !self.repeats.is_empty()
+            || self.elided.is_some()
+            || self
+                .cfg
+                .rules
+                .iter()
+                .any(|rule| rule.action == Action::Drop);
+
+        if may_drop {
+            (peeked, Some(remaining))
+        } else {
+            (remaining, Some(remaining))
+        }
+    }
+}
+
+/// Iterator over the shortened components of a line, paired with the delimiters that followed
+/// them.
+///
+/// Returned by [`ShortenedComponents::with_delimiters`].
+#[derive(Debug)]
+pub struct WithDelimiters<'a, 'l> {
+    inner: ShortenedComponents<'a, 'l>,
+}
+
+impl<'l> Iterator for WithDelimiters<'_, 'l> {
+    type Item = (Cow<'l, str>, Option<&'l str>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner
+            .next_segment()
+            .map(|segment| (segment.output, segment.delimiter))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
     }
-    shortened_elements
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use insta::assert_yaml_snapshot;
+    use insta::{assert_snapshot, assert_yaml_snapshot};
 
     #[test]
     fn shorten_line_works_with_default_config() {
@@ -161,7 +941,7 @@ mod tests {
         // GIVEN
         let line = "module,submodule,service,lib.rs";
         let cfg = Config {
-            delimiter: ",",
+            delimiter: Delimiter::from(","),
             ..Config::default()
         };
 
@@ -199,6 +979,52 @@ mod tests {
         ");
     }
 
+    #[cfg(feature = "fancy-regex")]
+    #[test]
+    fn shorten_line_supports_look_around_in_ignore_regex() {
+        // GIVEN
+        let line = "testutils/testdata/tests/lib.rs";
+        let re = Regex::with_engine("^test(?!utils)", Engine::FancyRegex).unwrap();
+        let cfg = Config {
+            ignore_regex: Some(re),
+            ..Config::default()
+        };
+
+        // WHEN
+        let result = shorten_line(&cfg, line);
+
+        // THEN
+        assert_yaml_snapshot!(result, @r"
+        - t
+        - testdata
+        - tests
+        - lib.rs
+        ");
+    }
+
+    #[cfg(all(feature = "regex", feature = "fancy-regex"))]
+    #[test]
+    fn shorten_line_supports_look_around_in_rules_for_the_engine_named() {
+        // GIVEN
+        let line = "testutils/testdata/tests/lib.rs";
+        let cfg = Config {
+            rules: vec!["fancy-regex:^test(?!utils) => keep".parse().unwrap()],
+            ..Config::default()
+        };
+
+        // WHEN
+        let result = shorten_line(&cfg, line);
+
+        // THEN
+        assert!("regex:^test(?!utils) => keep".parse::<Rule>().is_err());
+        assert_yaml_snapshot!(result, @r"
+        - t
+        - testdata
+        - tests
+        - lib.rs
+        ");
+    }
+
     #[test]
     fn shorten_line_ignores_last_n_components() {
         // GIVEN
@@ -353,7 +1179,7 @@ mod tests {
         // GIVEN
         let line = "/path/to/a/module/submodule/service/lib.rs";
         let cfg = Config {
-            delimiter: ":",
+            delimiter: Delimiter::from(":"),
             ..Config::default()
         };
 
@@ -361,7 +1187,7 @@ mod tests {
         let result = shorten_line(&cfg, line);
 
         // THEN
-        assert_yaml_snapshot!(result, @"- /path/to/a/module/submodule/service/lib.rs");
+        assert_yaml_snapshot!(result, @r"- /path/to/a/module/submodule/service/lib.rs");
     }
 
     #[test]
@@ -369,7 +1195,7 @@ mod tests {
         // GIVEN
         let line = "/path/to/lib.rs";
         let cfg = Config {
-            delimiter: "",
+            delimiter: Delimiter::from(""),
             ..Config::default()
         };
 
@@ -416,4 +1242,985 @@ mod tests {
         - ""
         "#);
     }
+
+    #[test]
+    fn shorten_line_uses_strategy() {
+        // GIVEN
+        let line = "module/submodule/service/lib.rs";
+        let cfg = Config {
+            strategy: Strategy::FirstAndLast,
+            ..Config::default()
+        };
+
+        // WHEN
+        let result = shorten_line(&cfg, line);
+
+        // THEN
+        assert_yaml_snapshot!(result, @r"
+        - m…e
+        - s…e
+        - s…e
+        - lib.rs
+        ");
+    }
+
+    #[test]
+    fn shorten_line_only_shortens_as_much_as_needed_to_fit_max_width() {
+        // GIVEN
+        let line = "module/submodule/service/lib.rs";
+        let cfg = Config {
+            max_width: Some(20),
+            ..Config::default()
+        };
+
+        // WHEN
+        let result = shorten_line(&cfg, line);
+
+        // THEN
+        assert_yaml_snapshot!(result, @r"
+        - m
+        - s
+        - service
+        - lib.rs
+        ");
+    }
+
+    #[test]
+    fn shorten_line_does_not_shorten_lines_within_max_width() {
+        // GIVEN
+        let line = "module/submodule/service/lib.rs";
+        let cfg = Config {
+            max_width: Some(31),
+            ..Config::default()
+        };
+
+        // WHEN
+        let result = shorten_line(&cfg, line);
+
+        // THEN
+        assert_yaml_snapshot!(result, @r"
+        - module
+        - submodule
+        - service
+        - lib.rs
+        ");
+    }
+
+    #[test]
+    fn shorten_line_shortens_everything_it_can_when_max_width_cannot_be_met() {
+        // GIVEN
+        let line = "module/submodule/service/lib.rs";
+        let cfg = Config {
+            max_width: Some(4),
+            ..Config::default()
+        };
+
+        // WHEN
+        let result = shorten_line(&cfg, line);
+
+        // THEN
+        assert_yaml_snapshot!(result, @r"
+        - m
+        - s
+        - s
+        - lib.rs
+        ");
+    }
+
+    #[test]
+    fn shorten_line_measures_max_width_in_display_columns() {
+        // GIVEN
+        // 4 + 1 + 4 + 1 + 2 + 1 + 6 = 19 columns, but 23 bytes/14 chars
+        let line = "模块/服务/🦀/lib.rs";
+        let cfg = Config {
+            max_width: Some(17),
+            ..Config::default()
+        };
+
+        // WHEN
+        let result = shorten_line(&cfg, line);
+
+        // THEN
+        assert_yaml_snapshot!(result, @r"
+        - 模
+        - 服务
+        - 🦀
+        - lib.rs
+        ");
+    }
+
+    #[test]
+    fn shorten_line_respects_output_delimiter_width_for_max_width() {
+        // GIVEN
+        let line = "module/submodule/service/lib.rs";
+        let cfg = Config {
+            max_width: Some(32),
+            output_delimiter: " / ".to_string(),
+            ..Config::default()
+        };
+
+        // WHEN
+        let result = shorten_line(&cfg, line);
+
+        // THEN
+        assert_yaml_snapshot!(result, @r"
+        - m
+        - submodule
+        - service
+        - lib.rs
+        ");
+    }
+
+    #[test]
+    fn shorten_line_works_with_a_set_of_delimiters() {
+        // GIVEN
+        let line = "com.example/service::Handler";
+        let cfg = Config {
+            delimiter: Delimiter::from(vec!["/", ".", "::"]),
+            ..Config::default()
+        };
+
+        // WHEN
+        let result = shorten_line(&cfg, line);
+
+        // THEN
+        assert_yaml_snapshot!(result, @r"
+        - c
+        - e
+        - s
+        - Handler
+        ");
+    }
+
+    #[test]
+    fn shorten_line_works_with_a_regex_delimiter() {
+        // GIVEN
+        let line = "module -> submodule => service -> lib.rs";
+        let cfg = Config {
+            delimiter: Delimiter::Regex(Regex::new(r"\s*[-=]>\s*").unwrap()),
+            ..Config::default()
+        };
+
+        // WHEN
+        let result = shorten_line(&cfg, line);
+
+        // THEN
+        assert_yaml_snapshot!(result, @r"
+        - m
+        - s
+        - s
+        - lib.rs
+        ");
+    }
+
+    #[test]
+    fn shorten_line_with_delimiters_returns_the_delimiters_that_followed_components() {
+        // GIVEN
+        let line = "/com.example/service::Handler";
+        let cfg = Config {
+            delimiter: Delimiter::from(vec!["/", ".", "::"]),
+            ..Config::default()
+        };
+
+        // WHEN
+        let result = shorten_line_with_delimiters(&cfg, line);
+
+        // THEN
+        assert_yaml_snapshot!(result, @r#"
+        - - ""
+          - /
+        - - c
+          - "."
+        - - e
+          - /
+        - - s
+          - "::"
+        - - Handler
+          - ~
+        "#);
+    }
+
+    #[test]
+    fn shorten_line_into_keeps_original_delimiters_if_configured() {
+        // GIVEN
+        let line = "com.example/service::Handler";
+        let cfg = Config {
+            delimiter: Delimiter::from(vec!["/", ".", "::"]),
+            output_delimiter: "|".to_string(),
+            keep_delimiters: true,
+            ..Config::default()
+        };
+        let mut buffer = String::new();
+
+        // WHEN
+        shorten_line_into(&cfg, line, &mut buffer).unwrap();
+
+        // THEN
+        assert_eq!(buffer, "c.e/s::Handler");
+    }
+
+    #[test]
+    fn shorten_line_measures_original_delimiters_for_max_width_if_kept() {
+        // GIVEN
+        let line = "com.example/service::Handler";
+        let cfg = Config {
+            delimiter: Delimiter::from(vec!["/", ".", "::"]),
+            keep_delimiters: true,
+            max_width: Some(27),
+            ..Config::default()
+        };
+        let mut buffer = String::new();
+
+        // WHEN
+        shorten_line_into(&cfg, line, &mut buffer).unwrap();
+
+        // THEN
+        // "com/example/service/Handler" would've fit in 27 columns
+        assert_eq!(buffer, "c.example/service::Handler");
+    }
+
+    #[test]
+    fn shorten_components_borrows_from_the_line() {
+        // GIVEN
+        let line = "module/submodule/service/lib.rs";
+
+        // WHEN
+        let result: Vec<_> = shorten_components(&Config::default(), line).collect();
+
+        // THEN
+        assert!(result.iter().all(|c| matches!(c, Cow::Borrowed(_))));
+        assert_yaml_snapshot!(result, @r"
+        - m
+        - s
+        - s
+        - lib.rs
+        ");
+    }
+
+    #[test]
+    fn shorten_components_keeps_multibyte_first_chars_intact() {
+        // GIVEN
+        let line = "über/ñandú/lib.rs";
+
+        // WHEN
+        let result: Vec<_> = shorten_components(&Config::default(), line).collect();
+
+        // THEN
+        assert_yaml_snapshot!(result, @r"
+        - ü
+        - ñ
+        - lib.rs
+        ");
+    }
+
+    #[test]
+    fn shorten_components_reports_exact_size() {
+        // GIVEN
+        let line = "/module/submodule/service/lib.rs";
+
+        let cfg = Config::default();
+
+        // WHEN
+        let mut components = shorten_components(&cfg, line);
+        components.next();
+
+        // THEN
+        assert_eq!(components.size_hint(), (4, Some(4)));
+        assert_eq!(components.count(), 4);
+    }
+
+    #[test]
+    fn shorten_line_into_uses_output_delimiter() {
+        // GIVEN
+        let line = "/module/submodule/service/lib.rs";
+        let cfg = Config {
+            output_delimiter: " > ".to_string(),
+            ..Config::default()
+        };
+        let mut buffer = String::from("path: ");
+
+        // WHEN
+        shorten_line_into(&cfg, line, &mut buffer).unwrap();
+
+        // THEN
+        assert_eq!(buffer, "path:  > m > s > s > lib.rs");
+    }
+
+    #[test]
+    fn shorten_line_applies_the_first_matching_rule() {
+        // GIVEN
+        let line = "src/main/scala/billing/api/utils/Server.scala";
+        let cfg = Config {
+            ignore_first_n: 1,
+            rules: vec![
+                "word:main => drop".parse().unwrap(),
+                "regex:^(billing|utils)$ => keep".parse().unwrap(),
+                "word:scala => alias:sc".parse().unwrap(),
+                "regex:^b => shorten".parse().unwrap(),
+                "position:-3 => strategy:first:2".parse().unwrap(),
+                "position:0 => shorten".parse().unwrap(),
+            ],
+            ..Config::default()
+        };
+
+        // WHEN
+        let result = shorten_line(&cfg, line);
+
+        // THEN
+        assert_yaml_snapshot!(result, @r"
+        - s
+        - sc
+        - billing
+        - ap
+        - utils
+        - Server.scala
+        ");
+    }
+
+    #[test]
+    fn shorten_line_into_drops_delimiters_next_to_dropped_components() {
+        // GIVEN
+        let lines = [
+            "src/main/scala/Server.scala",
+            "main/scala/Server.scala",
+            "scala/Server.scala/main",
+            "main",
+        ];
+        let cfg = Config {
+            delimiter: Delimiter::from(vec!["/", "."]),
+            keep_delimiters: true,
+            ignore_last_n: 2,
+            rules: vec!["word:main => drop".parse().unwrap()],
+            ..Config::default()
+        };
+
+        // WHEN
+        let result: Vec<_> = lines
+            .iter()
+            .map(|line| {
+                let mut buffer = String::new();
+                shorten_line_into(&cfg, line, &mut buffer).unwrap();
+                buffer
+            })
+            .collect();
+
+        // THEN
+        assert_yaml_snapshot!(result, @r#"
+        - s/s/Server.scala
+        - s/Server.scala
+        - s/S.scala
+        - ""
+        "#);
+    }
+
+    #[test]
+    fn shorten_line_accounts_for_rules_when_fitting_within_max_width() {
+        // GIVEN
+        let line = "src/main/scala/billing/Server.scala";
+        let cfg = Config {
+            rules: vec![
+                "word:src => drop".parse().unwrap(),
+                "word:scala => alias:sc".parse().unwrap(),
+            ],
+            max_width: Some(25),
+            ..Config::default()
+        };
+
+        // WHEN
+        let result = shorten_line(&cfg, line);
+
+        // THEN
+        assert_yaml_snapshot!(result, @r"
+        - m
+        - sc
+        - billing
+        - Server.scala
+        ");
+    }
+
+    #[test]
+    fn shorten_line_falls_back_to_strategy_for_components_not_matching_extraction() {
+        // GIVEN
+        let line = "services/service-billing-v2/service-api/lib.rs";
+        let cfg = Config {
+            extraction: Some(Extraction {
+                regex: Regex::new(r"service-(\w)\w*-(v\d+)").unwrap(),
+                replacement: "$1$2".to_string(),
+            }),
+            strategy: Strategy::FirstChars(3),
+            ..Config::default()
+        };
+
+        // WHEN
+        let result = shorten_line(&cfg, line);
+
+        // THEN
+        assert_yaml_snapshot!(result, @r"
+        - ser
+        - bv2
+        - ser
+        - lib.rs
+        ");
+    }
+
+    #[test]
+    fn shorten_line_keeps_extensions_and_leading_dots_if_filename_aware() {
+        // GIVEN
+        let line = "home/.config/backups/archive.tar.gz/ApplicationComponents.scala";
+        let cfg = Config {
+            ignore_last_n: 0,
+            filename_aware: true,
+            ..Config::default()
+        };
+
+        // WHEN
+        let result = shorten_line(&cfg, line);
+
+        // THEN
+        assert_yaml_snapshot!(result, @r#"
+        - h
+        - ".c"
+        - b
+        - a.tar.gz
+        - A.scala
+        "#);
+    }
+
+    #[test]
+    fn shorten_line_replaces_aliased_sequences_before_shortening() {
+        // GIVEN
+        let lines = [
+            "src/main/scala/admin/billing/Server.scala",
+            "src/test/scala/admin/billing/ServerSpec.scala",
+            "modules/src/main/scala/Server.scala",
+        ];
+        let cfg = Config {
+            ignore_first_n: 1,
+            aliases: vec![
+                Alias::new(["src", "main", "scala"], "main"),
+                Alias::new(["src", "test", "scala"], "test"),
+            ],
+            ..Config::default()
+        };
+
+        // WHEN
+        let result: Vec<_> = lines.iter().map(|l| shorten_line(&cfg, l)).collect();
+
+        // THEN
+        assert_yaml_snapshot!(result, @r"
+        - - main
+          - a
+          - b
+          - Server.scala
+        - - test
+          - a
+          - b
+          - ServerSpec.scala
+        - - modules
+          - main
+          - Server.scala
+        ");
+    }
+
+    #[test]
+    fn shorten_line_substitutes_prefixes_before_aliases() {
+        // GIVEN
+        let lines = [
+            "/home/alice/work/go/src/github.com/dhth/squidge/main.go",
+            "/home/alice/projects/src/main/scala/Server.scala",
+            "/home/alicia/projects/lib.rs",
+            "/home/alice",
+        ];
+        let cfg = Config {
+            ignore_first_n: 1,
+            prefixes: vec![
+                Prefix::new("/home/alice", "~"),
+                Prefix::new("/home/alice/work/go", "$GOPATH"),
+            ],
+            aliases: vec![Alias::new(["src", "main", "scala"], "main")],
+            ..Config::default()
+        };
+
+        // WHEN
+        let result: Vec<_> = lines.iter().map(|l| shorten_line(&cfg, l)).collect();
+
+        // THEN
+        assert_yaml_snapshot!(result, @r#"
+        - - $GOPATH
+          - s
+          - g
+          - d
+          - s
+          - main.go
+        - - "~"
+          - p
+          - main
+          - Server.scala
+        - - ""
+          - h
+          - a
+          - p
+          - lib.rs
+        - - "~"
+        "#);
+    }
+
+    #[test]
+    fn shorten_line_collapses_repeated_components() {
+        // GIVEN
+        let lines = [
+            "build/build/build/out/lib.rs",
+            "com/example/com/example/Foo.java",
+            "a/a/a/a",
+        ];
+        let runs = Config {
+            collapse: Collapse::Runs,
+            ..Config::default()
+        };
+        let blocks = Config {
+            collapse: Collapse::Blocks,
+            ..Config::default()
+        };
+
+        // WHEN
+        let result: Vec<_> = lines
+            .iter()
+            .map(|l| (shorten_line(&runs, l), shorten_line(&blocks, l)))
+            .collect();
+
+        // THEN
+        assert_yaml_snapshot!(result, @r"
+        - - - b×3
+            - o
+            - lib.rs
+          - - b×3
+            - o
+            - lib.rs
+        - - - c
+            - e
+            - c
+            - e
+            - Foo.java
+          - - (c/e)×2
+            - Foo.java
+        - - - a×4
+          - - a×4
+        ");
+    }
+
+    #[test]
+    fn shorten_line_into_collapses_blocks_with_rules_and_original_delimiters() {
+        // GIVEN
+        let lines = [
+            "com.example/com.example/Foo.java",
+            "src/main/src/main/src/main/lib.rs",
+            "main/main/lib.rs",
+        ];
+        let cfg = Config {
+            delimiter: Delimiter::from(vec!["/", "."]),
+            keep_delimiters: true,
+            ignore_last_n: 2,
+            rules: vec!["word:main => drop".parse().unwrap()],
+            collapse: Collapse::Blocks,
+            ..Config::default()
+        };
+
+        // WHEN
+        let result: Vec<_> = lines
+            .iter()
+            .map(|line| {
+                let mut buffer = String::new();
+                shorten_line_into(&cfg, line, &mut buffer).unwrap();
+                buffer
+            })
+            .collect();
+
+        // THEN
+        assert_yaml_snapshot!(result, @r"
+        - (c.e)×2/Foo.java
+        - s×3/lib.rs
+        - lib.rs
+        ");
+    }
+
+    #[test]
+    fn shorten_line_accounts_for_collapsed_components_when_fitting_within_max_width() {
+        // GIVEN
+        let line = "target/build/build/build/release/lib.rs";
+        let cfg = Config {
+            collapse: Collapse::Runs,
+            max_width: Some(29),
+            ..Config::default()
+        };
+
+        // WHEN
+        let result = shorten_line(&cfg, line);
+
+        // THEN
+        assert_yaml_snapshot!(result, @r"
+        - target
+        - build×3
+        - release
+        - lib.rs
+        ");
+    }
+
+    #[test]
+    fn shorten_line_elides_middle_components() {
+        // GIVEN
+        let lines = [
+            "src/main/scala/admin/billing/api/utils/v2/Server.scala",
+            "src/main/Server.scala",
+            "src/main/scala/Server.scala",
+        ];
+        let cfg = Config {
+            elision: Some(Elision {
+                keep_first: 1,
+                keep_last: 1,
+                marker: "…({count})".to_string(),
+            }),
+            ..Config::default()
+        };
+
+        // WHEN
+        let result: Vec<_> = lines.iter().map(|l| shorten_line(&cfg, l)).collect();
+
+        // THEN
+        assert_yaml_snapshot!(result, @r"
+        - - s
+          - …(7)
+          - Server.scala
+        - - s
+          - …(1)
+          - Server.scala
+        - - s
+          - …(2)
+          - Server.scala
+        ");
+    }
+
+    #[test]
+    fn shorten_line_into_elides_with_ignored_and_dropped_components() {
+        // GIVEN
+        let lines = [
+            "src/main/scala/admin/billing/api/utils/Server.scala",
+            "src/main/scala/admin/main/main/billing/Server.scala",
+            "src/main/main/main/billing/Server.scala",
+        ];
+        let cfg = Config {
+            ignore_first_n: 1,
+            ignore_last_n: 2,
+            ignore_regex: Some(Regex::new("^admin$").unwrap()),
+            rules: vec!["word:main => drop".parse().unwrap()],
+            collapse: Collapse::Runs,
+            elision: Some(Elision {
+                keep_first: 2,
+                keep_last: 2,
+                marker: "[{count} more]".to_string(),
+            }),
+            ..Config::default()
+        };
+
+        // WHEN
+        let result: Vec<_> = lines
+            .iter()
+            .map(|line| {
+                let mut buffer = String::new();
+                shorten_line_into(&cfg, line, &mut buffer).unwrap();
+                buffer
+            })
+            .collect();
+
+        // THEN
+        assert_yaml_snapshot!(result, @r#"
+        - "src/[4 more]/utils/Server.scala"
+        - "src/[2 more]/billing/Server.scala"
+        - src/billing/Server.scala
+        "#);
+    }
+
+    #[test]
+    fn shorten_line_accounts_for_the_elision_marker_when_fitting_within_max_width() {
+        // GIVEN
+        let line = "src/main/scala/admin/billing/api/Server.scala";
+        let cfg = Config {
+            elision: Some(Elision {
+                keep_first: 2,
+                keep_last: 2,
+                marker: "…".to_string(),
+            }),
+            max_width: Some(21),
+            ..Config::default()
+        };
+
+        // WHEN
+        let result = shorten_line(&cfg, line);
+
+        // THEN
+        assert_yaml_snapshot!(result, @r"
+        - s
+        - m
+        - …
+        - a
+        - Server.scala
+        ");
+    }
+
+    #[test]
+    fn shorten_line_into_applies_empty_component_policies() {
+        // GIVEN
+        let lines = [",module,,service,lib.rs,", "module,,,lib.rs", ",,"];
+        let configs = [
+            EmptyComponents::Keep,
+            EmptyComponents::Drop,
+            EmptyComponents::Placeholder("∅".to_string()),
+        ]
+        .map(|empty_components| Config {
+            delimiter: Delimiter::from(","),
+            output_delimiter: ",".to_string(),
+            ignore_first_n: 1,
+            empty_components,
+            ..Config::default()
+        });
+
+        // WHEN
+        let result: Vec<Vec<_>> = configs
+            .iter()
+            .map(|cfg| {
+                lines
+                    .iter()
+                    .map(|line| {
+                        let mut buffer = String::new();
+                        shorten_line_into(cfg, line, &mut buffer).unwrap();
+                        buffer
+                    })
+                    .collect()
+            })
+            .collect();
+
+        // THEN
+        assert_yaml_snapshot!(result, @r#"
+        - - ",m,,s,l,"
+          - "module,,,lib.rs"
+          - ",,"
+        - - "module,s,lib.rs"
+          - "module,lib.rs"
+          - ""
+        - - "∅,m,∅,s,l,∅"
+          - "module,∅,∅,lib.rs"
+          - "∅,∅,∅"
+        "#);
+    }
+
+    #[test]
+    fn shorten_line_into_preserves_the_absolute_root_without_counting_it() {
+        // GIVEN
+        let lines = [
+            "/home/user/projects/lib.rs",
+            "/usr//local/bin/",
+            "home/user/lib.rs",
+            "/",
+        ];
+        let cfg = Config {
+            ignore_first_n: 1,
+            empty_components: EmptyComponents::Drop,
+            absolute_root: true,
+            rules: vec!["position:-2 => keep".parse().unwrap()],
+            ..Config::default()
+        };
+
+        // WHEN
+        let result: Vec<_> = lines
+            .iter()
+            .map(|line| {
+                let mut buffer = String::new();
+                shorten_line_into(&cfg, line, &mut buffer).unwrap();
+                (buffer, shorten_components(&cfg, line).size_hint())
+            })
+            .collect();
+
+        // THEN
+        assert_yaml_snapshot!(result, @r"
+        - - /home/u/projects/lib.rs
+          - - 5
+            - 5
+        - - /usr/local/bin
+          - - 4
+            - 4
+        - - home/user/lib.rs
+          - - 3
+            - 3
+        - - /
+          - - 1
+            - 1
+        ");
+    }
+
+    #[test]
+    fn shorten_line_accounts_for_the_absolute_root_when_fitting_within_max_width() {
+        // GIVEN
+        let line = "/home/user/projects/lib.rs";
+        let cfg = Config {
+            absolute_root: true,
+            max_width: Some(19),
+            ..Config::default()
+        };
+
+        // WHEN
+        let result = shorten_line(&cfg, line);
+
+        // THEN
+        assert_yaml_snapshot!(result, @r#"
+        - ""
+        - h
+        - u
+        - p
+        - lib.rs
+        "#);
+    }
+
+    #[test]
+    fn shorten_line_ignores_components_chosen_by_index_selector() {
+        // GIVEN
+        let lines = [
+            "src/main/billing/api/utils/Server.scala",
+            "src/billing/Server.scala",
+            "/src/main/backup/Server.scala",
+        ];
+        let cfg = Config {
+            ignore_last_n: 0,
+            ignore_indices: Some("2,-2:".parse().unwrap()),
+            absolute_root: true,
+            ..Config::default()
+        };
+
+        // WHEN
+        let result: Vec<_> = lines.iter().map(|l| shorten_line(&cfg, l)).collect();
+
+        // THEN
+        assert_yaml_snapshot!(result, @r#"
+        - - s
+          - m
+          - billing
+          - a
+          - utils
+          - Server.scala
+        - - s
+          - billing
+          - Server.scala
+        - - ""
+          - s
+          - m
+          - backup
+          - Server.scala
+        "#);
+    }
+
+    #[test]
+    fn shorten_line_into_respects_quoted_components() {
+        // GIVEN
+        let lines = [
+            r#""Smith, John",Engineering,"R&D, Berlin""#,
+            r#""say ""hi""",x,"a ""quote""""#,
+            r#"5" disk,"unterminated, quote"#,
+            r#""quoted"trailing,x"#,
+        ];
+        let cfg = Config {
+            delimiter: Delimiter::from(","),
+            output_delimiter: ",".to_string(),
+            quoting: Some(Quoting::default()),
+            ..Config::default()
+        };
+
+        // WHEN
+        let result: Vec<_> = lines
+            .iter()
+            .map(|line| {
+                let mut buffer = String::new();
+                shorten_line_into(&cfg, line, &mut buffer).unwrap();
+                buffer
+            })
+            .collect();
+
+        // THEN
+        assert_yaml_snapshot!(result, @r#"
+        - "S,E,\"R&D, Berlin\""
+        - "s,x,\"a \"\"quote\"\"\""
+        - "5,\"\"\"\", quote"
+        - "\"\"\"\",x"
+        "#);
+    }
+
+    #[test]
+    fn shorten_line_into_shortens_the_unescaped_text_of_quoted_components() {
+        // GIVEN
+        let lines = [r#""""x""",ab"#, r#""a ""b"" c",x"#, r#""R&D, Berlin",x"#];
+        let cfg = Config {
+            delimiter: Delimiter::from(","),
+            output_delimiter: ",".to_string(),
+            quoting: Some(Quoting::default()),
+            strategy: Strategy::FirstChars(3),
+            ..Config::default()
+        };
+
+        // WHEN
+        let result: Vec<_> = lines
+            .iter()
+            .map(|line| {
+                let mut buffer = String::new();
+                shorten_line_into(&cfg, line, &mut buffer).unwrap();
+                buffer
+            })
+            .collect();
+
+        // THEN
+        assert_yaml_snapshot!(result, @r#"
+        - "\"\"\"x\"\"\",ab"
+        - "\"a \"\"\",x"
+        - "R&D,x"
+        "#);
+    }
+
+    #[test]
+    fn shorten_line_into_quotes_components_containing_the_output_delimiter() {
+        // GIVEN
+        let line = "src/main/billing's, backup/Server.scala";
+        let cfg = Config {
+            output_delimiter: ", ".to_string(),
+            quoting: Some(Quoting {
+                quote: '\'',
+                escape: '\\',
+            }),
+            ignore_indices: Some("2".parse().unwrap()),
+            ..Config::default()
+        };
+
+        // WHEN
+        let mut buffer = String::new();
+        shorten_line_into(&cfg, line, &mut buffer).unwrap();
+
+        // THEN
+        assert_snapshot!(buffer, @r"s, m, 'billing\'s, backup', Server.scala");
+    }
+
+    #[test]
+    fn shorten_line_into_counts_quotes_when_fitting_within_max_width() {
+        // GIVEN
+        // "x,y",module,lib would be 16 columns wide
+        let line = r#""x,y",module,lib"#;
+        let cfg = Config {
+            delimiter: Delimiter::from(","),
+            output_delimiter: ",".to_string(),
+            quoting: Some(Quoting::default()),
+            max_width: Some(14),
+            ..Config::default()
+        };
+
+        // WHEN
+        let mut buffer = String::new();
+        shorten_line_into(&cfg, line, &mut buffer).unwrap();
+
+        // THEN
+        assert_snapshot!(buffer, @"x,module,lib");
+    }
 }
@@ -0,0 +1,60 @@
+use crate::Config;
+use std::path::{Path, PathBuf};
+
+/// Shortens paths, which needn't be valid UTF-8.
+///
+/// Paths are shortened as lines split on [`Config::delimiter`], and joined as is done by
+/// [`shorten_line_into`](crate::shorten_line_into). On Unix, bytes that aren't valid UTF-8 are
+/// carried through unchanged (see [`shorten_bytes`](crate::shorten_bytes)); elsewhere, paths that
+/// aren't valid Unicode are shortened lossily.
+///
+/// Example:
+/// ```
+/// use squidge::{Config, PathExt};
+/// use std::path::{Path, PathBuf};
+///
+/// let path = Path::new("/home/user/projects/lib.rs");
+/// assert_eq!(path.shorten(&Config::default()), PathBuf::from("/h/u/p/lib.rs"));
+/// ```
+pub trait PathExt {
+    /// Shortens the path based on the provided configuration.
+    fn shorten(&self, cfg: &Config) -> PathBuf;
+}
+
+impl PathExt for Path {
+    #[cfg(unix)]
+    fn shorten(&self, cfg: &Config) -> PathBuf {
+        use std::ffi::OsString;
+        use std::os::unix::ffi::{OsStrExt, OsStringExt};
+
+        let shortened = crate::shorten_bytes(cfg, self.as_os_str().as_bytes());
+        PathBuf::from(OsString::from_vec(shortened))
+    }
+
+    #[cfg(not(unix))]
+    fn shorten(&self, cfg: &Config) -> PathBuf {
+        let mut shortened = String::new();
+        // writing to a String never fails
+        let _ = crate::shorten_line_into(cfg, &self.to_string_lossy(), &mut shortened);
+        PathBuf::from(shortened)
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::ffi::OsStr;
+    use std::os::unix::ffi::OsStrExt;
+
+    #[test]
+    fn shortening_paths_keeps_bytes_that_are_not_utf8() {
+        // GIVEN
+        let path = Path::new(OsStr::from_bytes(b"/home/caf\xe9/\xffprojects/lib\xfe.rs"));
+
+        // WHEN
+        let result = path.shorten(&Config::default());
+
+        // THEN
+        assert_eq!(result.as_os_str().as_bytes(), b"/h/c/\xff/lib\xfe.rs");
+    }
+}
@@ -0,0 +1,121 @@
+use crate::delimiter::{Delimiter, Split};
+use alloc::string::String;
+
+/// Replaces a known prefix of lines (eg, the home directory) with a symbolic token.
+///
+/// Prefixes listed in [`Config::prefixes`](crate::Config::prefixes) are substituted before
+/// anything else happens (including [aliases](crate::Alias)). A prefix only matches at the start of
+/// a line, and only if it's followed by a delimiter (or nothing at all), so `/home/alice` doesn't
+/// match `/home/alicia`; where several prefixes match, the longest one is used. The token counts as
+/// a single component (eg, for `ignore_first_n`), and is never shortened.
+///
+/// Example:
+/// ```
+/// use squidge::{Config, Prefix, shorten_line};
+///
+/// let cfg = Config {
+///     prefixes: vec![
+///         Prefix::new("/home/alice", "~"),
+///         Prefix::new("/home/alice/work/go", "$GOPATH"),
+///     ],
+///     ..Config::default()
+/// };
+/// let result = shorten_line(&cfg, "/home/alice/work/go/src/github.com/lib.go");
+/// assert_eq!(result, vec!["$GOPATH", "s", "g", "lib.go"]);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Prefix {
+    /// Text the line has to start with
+    pub path: String,
+    /// Token to replace it with
+    pub token: String,
+}
+
+impl Prefix {
+    /// Creates a prefix replacing `path` with `token`.
+    pub fn new(path: impl Into<String>, token: impl Into<String>) -> Self {
+        Prefix {
+            path: path.into(),
+            token: token.into(),
+        }
+    }
+}
+
+/// Finds the longest prefix `line` starts with, and returns its token, the text of the line it
+/// matched, the delimiter that follows it, and the split over the rest of the line.
+pub(crate) fn substitute<'a, 'l>(
+    prefixes: &'a [Prefix],
+    delimiter: &'a Delimiter,
+    line: &'l str,
+) -> Option<(&'a str, &'l str, Option<&'l str>, Split<'a, 'l>)> {
+    prefixes
+        .iter()
+        .filter(|prefix| !prefix.path.is_empty())
+        .filter_map(|prefix| {
+            let mut split = delimiter.split(line.strip_prefix(prefix.path.as_str())?);
+            match split.next()? {
+                (component, following) if component.is_empty() => Some((prefix, following, split)),
+                _ => None,
+            }
+        })
+        .rev()
+        .max_by_key(|(prefix, _, _)| prefix.path.len())
+        .map(|(prefix, following, split)| {
+            let source = &line[..prefix.path.len()];
+            (prefix.token.as_str(), source, following, split)
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec::Vec;
+    use insta::assert_yaml_snapshot;
+
+    #[test]
+    fn substitute_only_matches_whole_components() {
+        // GIVEN
+        let prefixes = [
+            Prefix::new("/home/alice", "~"),
+            Prefix::new("/home/alice/work/go", "$GOPATH"),
+            Prefix::new("", "empty"),
+        ];
+        let delimiter = Delimiter::from("/");
+        let lines = [
+            "/home/alice/work/lib.rs",
+            "/home/alice/work/go/src/lib.go",
+            "/home/alice",
+            "/home/alicia/lib.rs",
+            "home/alice/lib.rs",
+        ];
+
+        // WHEN
+        let result: Vec<_> = lines
+            .iter()
+            .map(|line| {
+                substitute(&prefixes, &delimiter, line).map(|(token, _, following, split)| {
+                    let rest: Vec<_> = split.map(|(c, _)| c).collect();
+                    (token, following, rest)
+                })
+            })
+            .collect();
+
+        // THEN
+        assert_yaml_snapshot!(result, @r#"
+        - - "~"
+          - /
+          - - work
+            - lib.rs
+        - - $GOPATH
+          - /
+          - - src
+            - lib.go
+        - - "~"
+          - ~
+          - []
+        - ~
+        - ~
+        "#);
+    }
+}
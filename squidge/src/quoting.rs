@@ -0,0 +1,223 @@
+use alloc::borrow::Cow;
+use alloc::string::String;
+
+/// Determines how quoted components are recognised when splitting a line, and how components are
+/// quoted when output.
+///
+/// A component that starts with `quote` runs up to the matching closing quote, so delimiters
+/// within it don't split it; it's treated as the text between the quotes, with escape sequences
+/// (`escape` followed by `quote` or `escape`) unescaped, and it's that text that is shortened. A
+/// component whose closing quote isn't followed by a delimiter (or the end of the line) is
+/// treated as is, quotes included, as is one whose quote is never closed.
+///
+/// Output components that contain the joining delimiter, or `quote`, are wrapped in quotes, and
+/// every quote (and escape character) in them is escaped. When the original delimiters are kept,
+/// components are quoted if [`Config::delimiter`](crate::Config::delimiter) matches in them.
+///
+/// Example:
+/// ```
+/// use squidge::{Config, Quoting, shorten_line_into};
+///
+/// let cfg = Config {
+///     delimiter: ",".into(),
+///     output_delimiter: ",".to_string(),
+///     quoting: Some(Quoting::default()),
+///     ..Config::default()
+/// };
+/// let mut buffer = String::new();
+/// shorten_line_into(&cfg, r#""Smith, John",Engineering,"R&D, Berlin""#, &mut buffer).unwrap();
+/// assert_eq!(buffer, r#"S,E,"R&D, Berlin""#);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct Quoting {
+    /// Character that quoted components start and end with
+    pub quote: char,
+    /// Character that escapes a quote within a quoted component; when it's the same as `quote`,
+    /// quotes are escaped by doubling them, as is done in CSV
+    pub escape: char,
+}
+
+impl Default for Quoting {
+    fn default() -> Self {
+        Quoting {
+            quote: '"',
+            escape: '"',
+        }
+    }
+}
+
+impl Quoting {
+    /// Returns the byte offset just past the closing quote of `text`, if it starts with a quote;
+    /// `None` if it doesn't, or if the quote is never closed.
+    pub(crate) fn closing(&self, text: &str) -> Option<usize> {
+        let rest = text.strip_prefix(self.quote)?;
+        let mut chars = rest.char_indices().peekable();
+        while let Some((i, c)) = chars.next() {
+            if c == self.escape && chars.peek().is_some_and(|&(_, next)| self.escapes(next)) {
+                chars.next();
+            } else if c == self.quote {
+                return Some(self.quote.len_utf8() + i + c.len_utf8());
+            }
+        }
+
+        None
+    }
+
+    /// Returns the text between the quotes of a quoted component, with its escape sequences
+    /// unescaped.
+    pub(crate) fn unescape<'c>(&self, text: &'c str) -> Cow<'c, str> {
+        if !text.contains(self.escape) {
+            return Cow::Borrowed(text);
+        }
+
+        let mut unescaped = String::with_capacity(text.len());
+        let mut chars = text.chars().peekable();
+        while let Some(c) = chars.next() {
+            match chars.peek() {
+                Some(&next) if c == self.escape && self.escapes(next) => {
+                    unescaped.push(next);
+                    chars.next();
+                }
+                _ => unescaped.push(c),
+            }
+        }
+
+        Cow::Owned(unescaped)
+    }
+
+    /// Wraps `component` in quotes, escaping the quotes (and escape characters) within it, if
+    /// `needs_quoting` says so, or if it contains a quote.
+    pub(crate) fn quote<'c>(
+        &self,
+        component: Cow<'c, str>,
+        needs_quoting: impl FnOnce(&str) -> bool,
+    ) -> Cow<'c, str> {
+        if !component.contains(self.quote) && !needs_quoting(&component) {
+            return component;
+        }
+
+        let mut quoted = String::with_capacity(component.len() + 2);
+        quoted.push(self.quote);
+        for c in component.chars() {
+            if self.escapes(c) {
+                quoted.push(self.escape);
+            }
+            quoted.push(c);
+        }
+        quoted.push(self.quote);
+
+        Cow::Owned(quoted)
+    }
+
+    /// Whether `c` is escaped when it follows the escape character.
+    fn escapes(&self, c: char) -> bool {
+        c == self.quote || c == self.escape
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec::Vec;
+    use insta::assert_yaml_snapshot;
+
+    #[test]
+    fn closing_skips_escaped_quotes() {
+        // GIVEN
+        let csv = Quoting::default();
+        let backslash = Quoting {
+            escape: '\\',
+            ..Quoting::default()
+        };
+
+        // WHEN
+        let result: Vec<_> = [
+            (&csv, r#""Smith, John",Engineering"#),
+            (&csv, r#""say ""hi""",x"#),
+            (&csv, r#""",x"#),
+            (&csv, r#""unterminated,x"#),
+            (&csv, "plain,x"),
+            (&backslash, r#""say \"hi\", \\",x"#),
+        ]
+        .into_iter()
+        .map(|(quoting, text)| quoting.closing(text).map(|end| &text[..end]))
+        .collect();
+
+        // THEN
+        assert_yaml_snapshot!(result, @r#"
+        - "\"Smith, John\""
+        - "\"say \"\"hi\"\"\""
+        - "\"\""
+        - ~
+        - ~
+        - "\"say \\\"hi\\\", \\\\\""
+        "#);
+    }
+
+    #[test]
+    fn unescape_unescapes_escape_sequences() {
+        // GIVEN
+        let csv = Quoting::default();
+        let backslash = Quoting {
+            escape: '\\',
+            ..Quoting::default()
+        };
+
+        // WHEN
+        let result: Vec<_> = [
+            (&csv, "Smith, John"),
+            (&csv, r#"say ""hi"""#),
+            (&csv, r#"5" disk"#),
+            (&backslash, r#"say \"hi\", \\"#),
+            (&backslash, r"C:\, D:\\"),
+        ]
+        .into_iter()
+        .map(|(quoting, text)| quoting.unescape(text))
+        .collect();
+
+        // THEN
+        assert_yaml_snapshot!(result, @r#"
+        - "Smith, John"
+        - "say \"hi\""
+        - "5\" disk"
+        - "say \"hi\", \\"
+        - "C:\\, D:\\"
+        "#);
+    }
+
+    #[test]
+    fn quote_escapes_quotes_and_escape_characters() {
+        // GIVEN
+        let csv = Quoting::default();
+        let backslash = Quoting {
+            escape: '\\',
+            ..Quoting::default()
+        };
+        let contains_comma = |c: &str| c.contains(',');
+
+        // WHEN
+        let result: Vec<_> = [
+            (&csv, "Engineering"),
+            (&csv, "R&D, Berlin"),
+            (&csv, r#"say "hi""#),
+            (&csv, r#"5" disk"#),
+            (&backslash, r#"say "hi", \"#),
+            (&backslash, r"C:\, D:\"),
+        ]
+        .into_iter()
+        .map(|(quoting, component)| quoting.quote(Cow::Borrowed(component), contains_comma))
+        .collect();
+
+        // THEN
+        assert_yaml_snapshot!(result, @r#"
+        - Engineering
+        - "\"R&D, Berlin\""
+        - "\"say \"\"hi\"\"\""
+        - "\"5\"\" disk\""
+        - "\"say \\\"hi\\\", \\\\\""
+        - "\"C:\\\\, D:\\\\\""
+        "#);
+    }
+}
@@ -0,0 +1,624 @@
+use alloc::string::{String, ToString};
+use core::fmt;
+use core::str::FromStr;
+
+// without an engine, `None` variants stand in for one, so that this is the only error reported
+#[cfg(not(any(
+    feature = "regex",
+    feature = "regex-lite",
+    feature = "fancy-regex",
+    feature = "regex-automata"
+)))]
+compile_error!(
+    "squidge needs a regex engine: enable at least one of the `regex`, `regex-lite`, \
+     `fancy-regex`, or `regex-automata` features"
+);
+
+/// Engines that regexes can be compiled with; each is available when the cargo feature of the
+/// same name is enabled, and is parsed from (and displayed as) that name (eg, `fancy-regex`).
+///
+/// The default engine is the first available one of `Regex`, `RegexAutomata`, `RegexLite`, and
+/// `FancyRegex`, so enabling another engine (say, via some other crate depending on squidge)
+/// never turns a default that supports the `regex` crate's syntax into one that doesn't. Regexes
+/// that need a particular engine (eg, for look-around) are to be compiled with it explicitly,
+/// via [`Regex::with_engine`], [`ConfigBuilder::engine`](crate::ConfigBuilder::engine), or a
+/// rule's matcher (see [`Rule`](crate::Rule)).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Engine {
+    /// The [`regex`](https://docs.rs/regex) crate
+    #[cfg(feature = "regex")]
+    Regex,
+    /// [`regex-automata`](https://docs.rs/regex-automata), which supports the same syntax as
+    /// `regex`, and only needs `alloc`
+    #[cfg(feature = "regex-automata")]
+    RegexAutomata,
+    /// [`regex-lite`](https://docs.rs/regex-lite), which is smaller (and slower), and has no
+    /// Unicode character classes
+    #[cfg(feature = "regex-lite")]
+    RegexLite,
+    /// [`fancy-regex`](https://docs.rs/fancy-regex), which supports look-around and
+    /// backreferences, and only needs `alloc`
+    #[cfg(feature = "fancy-regex")]
+    FancyRegex,
+    #[cfg(not(any(
+        feature = "regex",
+        feature = "regex-lite",
+        feature = "fancy-regex",
+        feature = "regex-automata"
+    )))]
+    #[doc(hidden)]
+    None,
+}
+
+impl Engine {
+    /// The available engines, in the order in which the default is picked.
+    pub(crate) const AVAILABLE: &[Engine] = &[
+        #[cfg(feature = "regex")]
+        Engine::Regex,
+        #[cfg(feature = "regex-automata")]
+        Engine::RegexAutomata,
+        #[cfg(feature = "regex-lite")]
+        Engine::RegexLite,
+        #[cfg(feature = "fancy-regex")]
+        Engine::FancyRegex,
+        #[cfg(not(any(
+            feature = "regex",
+            feature = "regex-lite",
+            feature = "fancy-regex",
+            feature = "regex-automata"
+        )))]
+        Engine::None,
+    ];
+}
+
+impl Default for Engine {
+    fn default() -> Self {
+        Engine::AVAILABLE[0]
+    }
+}
+
+impl fmt::Display for Engine {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            #[cfg(feature = "regex")]
+            Engine::Regex => write!(f, "regex"),
+            #[cfg(feature = "regex-automata")]
+            Engine::RegexAutomata => write!(f, "regex-automata"),
+            #[cfg(feature = "regex-lite")]
+            Engine::RegexLite => write!(f, "regex-lite"),
+            #[cfg(feature = "fancy-regex")]
+            Engine::FancyRegex => write!(f, "fancy-regex"),
+            #[cfg(not(any(
+                feature = "regex",
+                feature = "regex-lite",
+                feature = "fancy-regex",
+                feature = "regex-automata"
+            )))]
+            Engine::None => write!(f, "none"),
+        }
+    }
+}
+
+/// Error returned when parsing an [`Engine`] from a string fails.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseEngineError(String);
+
+impl fmt::Display for ParseEngineError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, r#"invalid regex engine "{}" (possible values: "#, self.0)?;
+        for (i, engine) in Engine::AVAILABLE.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{engine}")?;
+        }
+        write!(f, ")")
+    }
+}
+
+impl core::error::Error for ParseEngineError {}
+
+/// Only the available engines can be parsed.
+impl FromStr for Engine {
+    type Err = ParseEngineError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Engine::AVAILABLE
+            .iter()
+            .copied()
+            .find(|engine| engine.to_string() == s)
+            .ok_or_else(|| ParseEngineError(s.to_string()))
+    }
+}
+
+/// A compiled regex, as used by regex delimiters, ignore regexes, extractions, and regex rules.
+///
+/// Regexes are compiled via [`Engine::default`], unless an engine is picked explicitly. Whichever
+/// engine is used, regexes are applied to components in the same way.
+///
+/// Example:
+/// ```
+/// use squidge::Regex;
+///
+/// let re = Regex::new(r"^tests?$").unwrap();
+/// assert!(re.is_match("tests"));
+/// assert_eq!(re.as_str(), r"^tests?$");
+/// ```
+#[derive(Debug, Clone)]
+pub struct Regex(Backend);
+
+#[derive(Debug, Clone)]
+enum Backend {
+    #[cfg(feature = "regex")]
+    Regex(full::Regex),
+    #[cfg(feature = "regex-automata")]
+    RegexAutomata(automata::Regex),
+    #[cfg(feature = "regex-lite")]
+    RegexLite(lite::Regex),
+    #[cfg(feature = "fancy-regex")]
+    FancyRegex(fancy::Regex),
+    #[cfg(not(any(
+        feature = "regex",
+        feature = "regex-lite",
+        feature = "fancy-regex",
+        feature = "regex-automata"
+    )))]
+    None(none::Regex),
+}
+
+/// Calls the same method on whichever engine's regex (or error) `$value` holds.
+macro_rules! dispatch {
+    ($type:ident, $value:expr, $inner:ident => $body:expr) => {
+        match $value {
+            #[cfg(feature = "regex")]
+            $type::Regex($inner) => $body,
+            #[cfg(feature = "regex-automata")]
+            $type::RegexAutomata($inner) => $body,
+            #[cfg(feature = "regex-lite")]
+            $type::RegexLite($inner) => $body,
+            #[cfg(feature = "fancy-regex")]
+            $type::FancyRegex($inner) => $body,
+            #[cfg(not(any(
+                feature = "regex",
+                feature = "regex-lite",
+                feature = "fancy-regex",
+                feature = "regex-automata"
+            )))]
+            $type::None($inner) => $body,
+        }
+    };
+}
+
+impl Regex {
+    /// Compiles a regex from a pattern, via the default engine.
+    pub fn new(pattern: &str) -> Result<Regex, RegexError> {
+        Regex::with_engine(pattern, Engine::default())
+    }
+
+    /// Compiles a regex from a pattern, via `engine`.
+    ///
+    /// Example:
+    /// ```
+    /// # #[cfg(feature = "fancy-regex")] {
+    /// use squidge::{Engine, Regex};
+    ///
+    /// let re = Regex::with_engine("^test(?!utils)", Engine::FancyRegex).unwrap();
+    /// assert!(re.is_match("tests"));
+    /// assert!(!re.is_match("testutils"));
+    /// # }
+    /// ```
+    pub fn with_engine(pattern: &str, engine: Engine) -> Result<Regex, RegexError> {
+        let backend = match engine {
+            #[cfg(feature = "regex")]
+            Engine::Regex => full::Regex::new(pattern)
+                .map(Backend::Regex)
+                .map_err(ErrorRepr::Regex),
+            #[cfg(feature = "regex-automata")]
+            Engine::RegexAutomata => automata::Regex::new(pattern)
+                .map(Backend::RegexAutomata)
+                .map_err(ErrorRepr::RegexAutomata),
+            #[cfg(feature = "regex-lite")]
+            Engine::RegexLite => lite::Regex::new(pattern)
+                .map(Backend::RegexLite)
+                .map_err(ErrorRepr::RegexLite),
+            #[cfg(feature = "fancy-regex")]
+            Engine::FancyRegex => fancy::Regex::new(pattern)
+                .map(Backend::FancyRegex)
+                .map_err(ErrorRepr::FancyRegex),
+            #[cfg(not(any(
+                feature = "regex",
+                feature = "regex-lite",
+                feature = "fancy-regex",
+                feature = "regex-automata"
+            )))]
+            Engine::None => none::Regex::new(pattern)
+                .map(Backend::None)
+                .map_err(ErrorRepr::None),
+        };
+
+        backend.map(Regex).map_err(RegexError)
+    }
+
+    /// Returns the engine the regex was compiled with.
+    pub fn engine(&self) -> Engine {
+        match self.0 {
+            #[cfg(feature = "regex")]
+            Backend::Regex(_) => Engine::Regex,
+            #[cfg(feature = "regex-automata")]
+            Backend::RegexAutomata(_) => Engine::RegexAutomata,
+            #[cfg(feature = "regex-lite")]
+            Backend::RegexLite(_) => Engine::RegexLite,
+            #[cfg(feature = "fancy-regex")]
+            Backend::FancyRegex(_) => Engine::FancyRegex,
+            #[cfg(not(any(
+                feature = "regex",
+                feature = "regex-lite",
+                feature = "fancy-regex",
+                feature = "regex-automata"
+            )))]
+            Backend::None(_) => Engine::None,
+        }
+    }
+
+    /// Returns the pattern the regex was compiled from.
+    pub fn as_str(&self) -> &str {
+        dispatch!(Backend, &self.0, re => re.as_str())
+    }
+
+    /// Returns what regexes are compared by: their patterns and engines.
+    pub(crate) fn key(&self) -> (&str, Engine) {
+        (self.as_str(), self.engine())
+    }
+
+    /// Whether the regex matches anywhere in `haystack`.
+    pub fn is_match(&self, haystack: &str) -> bool {
+        dispatch!(Backend, &self.0, re => re.is_match(haystack))
+    }
+
+    /// Returns the byte range of the leftmost match in `haystack` that starts at or after
+    /// `start`; the text before `start` is still considered by anchors and word boundaries.
+    pub(crate) fn find_at(&self, haystack: &str, start: usize) -> Option<(usize, usize)> {
+        dispatch!(Backend, &self.0, re => re.find_at(haystack, start))
+    }
+
+    /// Returns `replacement`, with references to capture groups (`$1`, `${name}`, etc.) expanded,
+    /// if the regex matches `haystack`.
+    pub(crate) fn expand(&self, haystack: &str, replacement: &str) -> Option<String> {
+        dispatch!(Backend, &self.0, re => re.expand(haystack, replacement))
+    }
+}
+
+impl FromStr for Regex {
+    type Err = RegexError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Regex::new(s)
+    }
+}
+
+impl fmt::Display for Regex {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// Error returned when a regex couldn't be compiled.
+#[derive(Debug, Clone)]
+pub struct RegexError(ErrorRepr);
+
+#[derive(Debug, Clone)]
+enum ErrorRepr {
+    #[cfg(feature = "regex")]
+    Regex(full::Error),
+    #[cfg(feature = "regex-automata")]
+    RegexAutomata(automata::Error),
+    #[cfg(feature = "regex-lite")]
+    RegexLite(lite::Error),
+    #[cfg(feature = "fancy-regex")]
+    FancyRegex(fancy::Error),
+    #[cfg(not(any(
+        feature = "regex",
+        feature = "regex-lite",
+        feature = "fancy-regex",
+        feature = "regex-automata"
+    )))]
+    None(none::Error),
+}
+
+impl fmt::Display for RegexError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        dispatch!(ErrorRepr, &self.0, e => write!(f, "{e}"))
+    }
+}
+
+impl core::error::Error for RegexError {}
+
+/// Defines a backend for an engine whose API matches that of the `regex` crate.
+#[cfg(any(feature = "regex", feature = "regex-lite"))]
+macro_rules! regex_like_backend {
+    ($name:ident, $engine:ident) => {
+        mod $name {
+            use alloc::string::String;
+
+            pub(super) type Error = $engine::Error;
+
+            #[derive(Debug, Clone)]
+            pub(super) struct Regex($engine::Regex);
+
+            impl Regex {
+                pub(super) fn new(pattern: &str) -> Result<Self, Error> {
+                    $engine::Regex::new(pattern).map(Regex)
+                }
+
+                pub(super) fn as_str(&self) -> &str {
+                    self.0.as_str()
+                }
+
+                pub(super) fn is_match(&self, haystack: &str) -> bool {
+                    self.0.is_match(haystack)
+                }
+
+                pub(super) fn find_at(
+                    &self,
+                    haystack: &str,
+                    start: usize,
+                ) -> Option<(usize, usize)> {
+                    self.0
+                        .find_at(haystack, start)
+                        .map(|m| (m.start(), m.end()))
+                }
+
+                pub(super) fn expand(&self, haystack: &str, replacement: &str) -> Option<String> {
+                    let captures = self.0.captures(haystack)?;
+                    let mut expanded = String::new();
+                    captures.expand(replacement, &mut expanded);
+
+                    Some(expanded)
+                }
+            }
+        }
+    };
+}
+
+#[cfg(feature = "regex")]
+regex_like_backend!(full, regex);
+
+#[cfg(feature = "regex-lite")]
+regex_like_backend!(lite, regex_lite);
+
+#[cfg(feature = "fancy-regex")]
+mod fancy {
+    use alloc::boxed::Box;
+    use alloc::string::String;
+
+    // errors are large, and would bloat every `Result` they're in
+    pub(super) type Error = Box<fancy_regex::Error>;
+
+    /// Matching can fail at runtime (eg, when the backtrack limit is hit); such failures are
+    /// treated as the regex not matching.
+    #[derive(Debug, Clone)]
+    pub(super) struct Regex(fancy_regex::Regex);
+
+    impl Regex {
+        pub(super) fn new(pattern: &str) -> Result<Self, Error> {
+            fancy_regex::Regex::new(pattern)
+                .map(Regex)
+                .map_err(Box::new)
+        }
+
+        pub(super) fn as_str(&self) -> &str {
+            self.0.as_str()
+        }
+
+        pub(super) fn is_match(&self, haystack: &str) -> bool {
+            self.0.is_match(haystack).unwrap_or(false)
+        }
+
+        pub(super) fn find_at(&self, haystack: &str, start: usize) -> Option<(usize, usize)> {
+            self.0
+                .find_from_pos(haystack, start)
+                .ok()
+                .flatten()
+                .map(|m| (m.start(), m.end()))
+        }
+
+        pub(super) fn expand(&self, haystack: &str, replacement: &str) -> Option<String> {
+            let captures = self.0.captures(haystack).ok().flatten()?;
+            let mut expanded = String::new();
+            captures.expand(replacement, &mut expanded);
+
+            Some(expanded)
+        }
+    }
+}
+
+#[cfg(feature = "regex-automata")]
+mod automata {
+    use alloc::boxed::Box;
+    use alloc::string::String;
+    use regex_automata::Input;
+    use regex_automata::meta;
+
+    // build errors are large, and would bloat every `Result` they're in
+    pub(super) type Error = Box<meta::BuildError>;
+
+    /// regex-automata's regexes don't keep their patterns around, so they're stored alongside.
+    #[derive(Debug, Clone)]
+    pub(super) struct Regex {
+        regex: meta::Regex,
+        pattern: String,
+    }
+
+    impl Regex {
+        pub(super) fn new(pattern: &str) -> Result<Self, Error> {
+            meta::Regex::new(pattern)
+                .map(|regex| Regex {
+                    regex,
+                    pattern: String::from(pattern),
+                })
+                .map_err(Box::new)
+        }
+
+        pub(super) fn as_str(&self) -> &str {
+            &self.pattern
+        }
+
+        pub(super) fn is_match(&self, haystack: &str) -> bool {
+            self.regex.is_match(haystack)
+        }
+
+        pub(super) fn find_at(&self, haystack: &str, start: usize) -> Option<(usize, usize)> {
+            self.regex
+                .find(Input::new(haystack).range(start..))
+                .map(|m| (m.start(), m.end()))
+        }
+
+        pub(super) fn expand(&self, haystack: &str, replacement: &str) -> Option<String> {
+            let mut captures = self.regex.create_captures();
+            self.regex.captures(haystack, &mut captures);
+            if !captures.is_match() {
+                return None;
+            }
+
+            let mut expanded = String::new();
+            captures.interpolate_string_into(haystack, replacement, &mut expanded);
+
+            Some(expanded)
+        }
+    }
+}
+
+#[cfg(not(any(
+    feature = "regex",
+    feature = "regex-lite",
+    feature = "fancy-regex",
+    feature = "regex-automata"
+)))]
+mod none {
+    use alloc::string::String;
+
+    pub(super) type Error = core::convert::Infallible;
+
+    #[derive(Debug, Clone)]
+    pub(super) struct Regex;
+
+    impl Regex {
+        pub(super) fn new(_pattern: &str) -> Result<Self, Error> {
+            unreachable!()
+        }
+
+        pub(super) fn as_str(&self) -> &str {
+            unreachable!()
+        }
+
+        pub(super) fn is_match(&self, _haystack: &str) -> bool {
+            unreachable!()
+        }
+
+        pub(super) fn find_at(&self, _haystack: &str, _start: usize) -> Option<(usize, usize)> {
+            unreachable!()
+        }
+
+        pub(super) fn expand(&self, _haystack: &str, _replacement: &str) -> Option<String> {
+            unreachable!()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec::Vec;
+    use insta::assert_yaml_snapshot;
+
+    #[test]
+    fn find_at_considers_the_text_before_start() {
+        // GIVEN
+        let haystack = "main/domain/main";
+
+        // WHEN
+        let results: Vec<Vec<_>> = Engine::AVAILABLE
+            .iter()
+            .map(|&engine| {
+                let re = Regex::with_engine(r"\bmain", engine).unwrap();
+                [0, 1, 5, 7]
+                    .into_iter()
+                    .map(|start| re.find_at(haystack, start))
+                    .collect()
+            })
+            .collect();
+
+        // THEN
+        assert!(results.iter().all(|result| *result == results[0]));
+        assert_yaml_snapshot!(results[0], @r"
+        - - 0
+          - 4
+        - - 12
+          - 16
+        - - 12
+          - 16
+        - - 12
+          - 16
+        ");
+    }
+
+    #[test]
+    fn expand_expands_capture_groups_of_matches() {
+        // GIVEN
+        let haystacks = ["billing-v2", "billing"];
+
+        // WHEN
+        let results: Vec<Vec<_>> = Engine::AVAILABLE
+            .iter()
+            .map(|&engine| {
+                let re = Regex::with_engine(r"^(?<name>\w+)-(v\d+)$", engine).unwrap();
+                haystacks
+                    .into_iter()
+                    .map(|haystack| re.expand(haystack, "${name}:$2$$"))
+                    .collect()
+            })
+            .collect();
+
+        // THEN
+        assert!(results.iter().all(|result| *result == results[0]));
+        assert_yaml_snapshot!(results[0], @r#"
+        - "billing:v2$"
+        - ~
+        "#);
+    }
+
+    #[test]
+    fn engines_are_parsed_from_their_names() {
+        for &engine in Engine::AVAILABLE {
+            // GIVEN
+            // WHEN
+            let result = engine.to_string().parse::<Engine>();
+
+            // THEN
+            assert_eq!(result, Ok(engine));
+        }
+    }
+
+    #[test]
+    fn parsing_an_unknown_engine_fails() {
+        // GIVEN
+        // WHEN
+        let result = "pcre".parse::<Engine>().unwrap_err().to_string();
+
+        // THEN
+        assert!(result.starts_with(r#"invalid regex engine "pcre" (possible values: "#));
+    }
+
+    #[test]
+    fn regexes_are_compiled_with_the_engine_they_are_asked_for() {
+        for &engine in Engine::AVAILABLE {
+            // GIVEN
+            // WHEN
+            let re = Regex::with_engine("a+", engine).unwrap();
+
+            // THEN
+            assert_eq!(re.engine(), engine);
+        }
+    }
+}
@@ -0,0 +1,349 @@
+use crate::{Engine, Regex, Strategy};
+use alloc::format;
+use alloc::string::{String, ToString};
+use core::fmt;
+use core::str::FromStr;
+
+const RULE_SEPARATOR: &str = " => ";
+
+/// Decides what happens to the components it matches.
+///
+/// Rules are listed in [`Config::rules`](crate::Config::rules), and are consulted in order for
+/// every component; the first one that matches determines what happens to it. Components no rule
+/// matches are handled as per the rest of the config (`ignore_first_n`, `ignore_last_n`,
+/// `ignore_regex` and `strategy`).
+///
+/// Rules can also be parsed from strings of the form `<matcher> => <action>`, where the matcher is
+/// one of `regex:PATTERN`, `ENGINE:PATTERN`, `word:WORD` or `position:INDEX`, and the action is one
+/// of `keep`, `shorten`, `alias:TEXT`, `drop` or `strategy:STRATEGY`. `regex:PATTERN` is compiled
+/// with the default [`Engine`]; `ENGINE:PATTERN` (eg, `fancy-regex:^test(?!utils)`) with the one
+/// named.
+///
+/// Example:
+/// ```
+/// use squidge::{Action, Config, Matcher, Rule, shorten_line};
+///
+/// let cfg = Config {
+///     rules: vec![
+///         "word:src => drop".parse().unwrap(),
+///         "regex:^(billing|utils)$ => keep".parse().unwrap(),
+///         Rule {
+///             matcher: Matcher::Word("scala".to_string()),
+///             action: Action::Alias("sc".to_string()),
+///         },
+///         "position:-2 => strategy:first:3".parse().unwrap(),
+///     ],
+///     ..Config::default()
+/// };
+/// let result = shorten_line(&cfg, "src/main/scala/billing/api/Server.scala");
+/// assert_eq!(result, vec!["m", "sc", "billing", "api", "Server.scala"]);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Rule {
+    /// Determines which components the rule applies to
+    pub matcher: Matcher,
+    /// What happens to the matched components
+    pub action: Action,
+}
+
+/// Determines which components a [`Rule`] applies to.
+#[derive(Debug, Clone)]
+pub enum Matcher {
+    /// Components matching a regex
+    Regex(Regex),
+    /// Components equal to a word
+    Word(String),
+    /// The component at an index; negative indices count from the end (-1 is the last component)
+    Position(isize),
+}
+
+/// What happens to the components a [`Rule`] matches.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Action {
+    /// Keep the component as is
+    Keep,
+    /// Shorten the component as per the config
+    Shorten,
+    /// Replace the component with a fixed alias
+    Alias(String),
+    /// Remove the component (along with a delimiter next to it) from the output
+    Drop,
+    /// Shorten the component using the given strategy instead of the config's
+    Strategy(Strategy),
+}
+
+/// What is to be done to a single component, as determined by a [`Config`](crate::Config).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum Decision<'a> {
+    Keep,
+    Shorten,
+    Abbreviate(&'a Strategy),
+    Alias(&'a str),
+    Drop,
+}
+
+impl Matcher {
+    /// Whether the component at index `i` (out of `num_components`) is matched.
+    pub(crate) fn matches(&self, i: usize, num_components: usize, component: &str) -> bool {
+        match self {
+            Matcher::Regex(re) => re.is_match(component),
+            Matcher::Word(word) => word == component,
+            Matcher::Position(position) if *position >= 0 => position.unsigned_abs() == i,
+            Matcher::Position(position) => {
+                num_components.checked_sub(position.unsigned_abs()) == Some(i)
+            }
+        }
+    }
+}
+
+impl Action {
+    pub(crate) fn decision(&self) -> Decision<'_> {
+        match self {
+            Action::Keep => Decision::Keep,
+            Action::Shorten => Decision::Shorten,
+            Action::Alias(alias) => Decision::Alias(alias),
+            Action::Drop => Decision::Drop,
+            Action::Strategy(strategy) => Decision::Abbreviate(strategy),
+        }
+    }
+}
+
+/// Regex matchers are compared by their patterns and engines.
+impl PartialEq for Matcher {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Matcher::Regex(a), Matcher::Regex(b)) => a.key() == b.key(),
+            (Matcher::Word(a), Matcher::Word(b)) => a == b,
+            (Matcher::Position(a), Matcher::Position(b)) => a == b,
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Display for Matcher {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Matcher::Regex(re) if re.engine() == Engine::default() => {
+                write!(f, "regex:{}", re.as_str())
+            }
+            Matcher::Regex(re) => write!(f, "{}:{}", re.engine(), re.as_str()),
+            Matcher::Word(word) => write!(f, "word:{word}"),
+            Matcher::Position(position) => write!(f, "position:{position}"),
+        }
+    }
+}
+
+impl fmt::Display for Action {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Action::Keep => write!(f, "keep"),
+            Action::Shorten => write!(f, "shorten"),
+            Action::Alias(alias) => write!(f, "alias:{alias}"),
+            Action::Drop => write!(f, "drop"),
+            Action::Strategy(strategy) => write!(f, "strategy:{strategy}"),
+        }
+    }
+}
+
+impl fmt::Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{RULE_SEPARATOR}{}", self.matcher, self.action)
+    }
+}
+
+/// Error returned when parsing a [`Rule`] from a string fails.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseRuleError(String);
+
+impl fmt::Display for ParseRuleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl core::error::Error for ParseRuleError {}
+
+impl FromStr for Matcher {
+    type Err = ParseRuleError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.split_once(':') {
+            Some(("regex", pattern)) => regex_matcher(pattern, Engine::default()),
+            Some(("word", word)) => Ok(Matcher::Word(word.to_string())),
+            Some(("position", position)) => position
+                .parse()
+                .map(Matcher::Position)
+                .map_err(|_| ParseRuleError(format!(r#"invalid position "{position}""#))),
+            Some((engine, pattern)) if let Ok(engine) = engine.parse() => {
+                regex_matcher(pattern, engine)
+            }
+            _ => Err(ParseRuleError(format!(
+                r#"invalid matcher "{s}" (possible values: regex:PATTERN, ENGINE:PATTERN, word:WORD, position:INDEX)"#
+            ))),
+        }
+    }
+}
+
+fn regex_matcher(pattern: &str, engine: Engine) -> Result<Matcher, ParseRuleError> {
+    Regex::with_engine(pattern, engine)
+        .map(Matcher::Regex)
+        .map_err(|e| ParseRuleError(format!(r#"couldn't compile regex "{pattern}": {e}"#)))
+}
+
+impl FromStr for Action {
+    type Err = ParseRuleError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.split_once(':') {
+            None if s == "keep" => Ok(Action::Keep),
+            None if s == "shorten" => Ok(Action::Shorten),
+            None if s == "drop" => Ok(Action::Drop),
+            Some(("alias", alias)) => Ok(Action::Alias(alias.to_string())),
+            Some(("strategy", strategy)) => strategy
+                .parse()
+                .map(Action::Strategy)
+                .map_err(|e| ParseRuleError(e.to_string())),
+            _ => Err(ParseRuleError(format!(
+                r#"invalid action "{s}" (possible values: keep, shorten, alias:TEXT, drop, strategy:STRATEGY)"#
+            ))),
+        }
+    }
+}
+
+impl FromStr for Rule {
+    type Err = ParseRuleError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // regexes are more likely to contain the separator than actions are
+        let (matcher, action) = s.trim().rsplit_once(RULE_SEPARATOR).ok_or_else(|| {
+            ParseRuleError(format!(
+                r#"invalid rule "{s}" (expected "<matcher>{RULE_SEPARATOR}<action>")"#
+            ))
+        })?;
+
+        Ok(Rule {
+            matcher: matcher.parse()?,
+            action: action.parse()?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec::Vec;
+    use insta::{assert_snapshot, assert_yaml_snapshot};
+
+    #[test]
+    fn position_matcher_counts_negative_indices_from_the_end() {
+        // GIVEN
+        let components = ["src", "main", "lib.rs"];
+
+        // WHEN
+        let result: Vec<_> = [0, 2, 3, -1, -3, -4]
+            .into_iter()
+            .map(|p| {
+                components
+                    .iter()
+                    .enumerate()
+                    .filter(|(i, c)| Matcher::Position(p).matches(*i, components.len(), c))
+                    .map(|(_, c)| *c)
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+
+        // THEN
+        assert_yaml_snapshot!(result, @r"
+        - - src
+        - - lib.rs
+        - []
+        - - lib.rs
+        - - src
+        - []
+        ");
+    }
+
+    #[test]
+    fn parsing_rules_works() {
+        // GIVEN
+        let rules = [
+            "regex:^(billing|utils)$ => keep",
+            "regex:a => b => shorten",
+            "word:scala => alias:sc",
+            "word:main => drop",
+            "position:-2 => strategy:last:3",
+        ];
+
+        // WHEN
+        let result: Vec<_> = rules
+            .into_iter()
+            .map(|r| r.parse::<Rule>().unwrap())
+            .collect();
+
+        // THEN
+        assert_eq!(
+            result[1].matcher,
+            Matcher::Regex(Regex::new("a => b").unwrap())
+        );
+        assert_eq!(result[4].action, Action::Strategy(Strategy::LastChars(3)));
+        assert_eq!(
+            result.iter().map(Rule::to_string).collect::<Vec<_>>(),
+            rules
+        );
+    }
+
+    #[test]
+    fn parsing_rules_compiles_regexes_with_the_engine_named() {
+        for &engine in Engine::AVAILABLE {
+            // GIVEN
+            let rule = format!("{engine}:^tests?$ => keep");
+
+            // WHEN
+            let result = rule.parse::<Rule>().unwrap();
+
+            // THEN
+            let Matcher::Regex(re) = &result.matcher else {
+                panic!("expected a regex matcher, got {}", result.matcher);
+            };
+            assert_eq!(re.engine(), engine);
+            assert_eq!(result.to_string().parse::<Rule>(), Ok(result));
+        }
+    }
+
+    #[test]
+    fn parsing_rules_fails_for_invalid_input() {
+        // GIVEN
+        let rules = [
+            "word:main",
+            "name:main => keep",
+            "position:first => keep",
+            "word:main => rename:m",
+            "word:main => strategy:middle",
+        ];
+
+        // WHEN
+        let result: Vec<_> = rules
+            .into_iter()
+            .map(|r| r.parse::<Rule>().unwrap_err().to_string())
+            .collect();
+        let regex_err = "regex:(main => keep"
+            .parse::<Rule>()
+            .unwrap_err()
+            .to_string();
+
+        // THEN
+        assert_snapshot!(result.join("\n"), @r#"
+        invalid rule "word:main" (expected "<matcher> => <action>")
+        invalid matcher "name:main" (possible values: regex:PATTERN, ENGINE:PATTERN, word:WORD, position:INDEX)
+        invalid position "first"
+        invalid action "rename:m" (possible values: keep, shorten, alias:TEXT, drop, strategy:STRATEGY)
+        invalid strategy "middle" (possible values: first, first:N, last, last:N, first-last)
+        "#);
+        // the regex error's details depend on the engine
+        let compile_err = Regex::new("(main").unwrap_err();
+        assert_eq!(
+            regex_err,
+            format!(r#"couldn't compile regex "(main": {compile_err}"#)
+        );
+    }
+}
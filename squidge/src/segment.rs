@@ -0,0 +1,272 @@
+use crate::{Config, shorten_components};
+use alloc::borrow::Cow;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+use core::ops::Range;
+
+/// A line shortened as per a [`Config`], made up of [`Segment`]s that record where each
+/// component of the output came from, and why it looks the way it does.
+///
+/// A shortened line renders (via [`fmt::Display`]) with its segments joined by
+/// [`Config::output_delimiter`] (or the original delimiters, if [`Config::keep_delimiters`] is
+/// set); [`ShortenedLine::with_delimiter`] and [`ShortenedLine::with_original_delimiters`] change
+/// that.
+///
+/// Example:
+/// ```
+/// use squidge::{Config, Reason, ShortenedLine};
+///
+/// let cfg = Config {
+///     ignore_first_n: 1,
+///     ..Config::default()
+/// };
+/// let line = ShortenedLine::new(&cfg, "module/submodule/lib.rs");
+///
+/// let segments: Vec<_> = line
+///     .segments()
+///     .iter()
+///     .map(|segment| (segment.original, segment.span.clone(), segment.reason))
+///     .collect();
+/// assert_eq!(
+///     segments,
+///     vec![
+///         ("module", 0..6, Reason::IgnoredFirstN),
+///         ("submodule", 7..16, Reason::Shortened),
+///         ("lib.rs", 17..23, Reason::IgnoredLastN),
+///     ]
+/// );
+/// assert_eq!(line.to_string(), "module/s/lib.rs");
+/// assert_eq!(line.with_delimiter(" > ").to_string(), "module > s > lib.rs");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShortenedLine<'l> {
+    segments: Vec<Segment<'l>>,
+    delimiter: Option<String>,
+}
+
+/// A component of a [`ShortenedLine`], along with its provenance.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Segment<'l> {
+    /// Text of the line the segment stands for; this spans several components for aliases,
+    /// prefixes, and collapsed or elided components, and leaves out the quotes of quoted ones
+    pub original: &'l str,
+    /// Text the segment is output as
+    pub output: Cow<'l, str>,
+    /// Byte range of `original` in the line
+    pub span: Range<usize>,
+    /// Delimiter that followed the segment in the line; `None` if it's the last one output
+    pub delimiter: Option<&'l str>,
+    /// Why the segment is output the way it is
+    pub reason: Reason,
+}
+
+/// Why a [`Segment`] is output the way it is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Reason {
+    /// Kept as is, being among the first [`Config::ignore_first_n`] components
+    IgnoredFirstN,
+    /// Kept as is, being among the last [`Config::ignore_last_n`] components
+    IgnoredLastN,
+    /// Kept as is, being chosen by [`Config::ignore_indices`]
+    IgnoredIndex,
+    /// Kept as is, matching [`Config::ignore_regex`]
+    MatchedIgnoreRegex,
+    /// Abbreviated as per [`Config::extraction`] or [`Config::strategy`]
+    Shortened,
+    /// Left as is, since the line fits within [`Config::max_width`] without shortening it
+    FitsMaxWidth,
+    /// Empty component, output as per [`Config::empty_components`]
+    Empty,
+    /// Output as per the rule at this index in [`Config::rules`]
+    Rule(usize),
+    /// Token of a prefix in [`Config::prefixes`]
+    Prefix,
+    /// Token of an alias in [`Config::aliases`]
+    Alias,
+    /// Root of an absolute line (see [`Config::absolute_root`])
+    Root,
+    /// Repeated components collapsed as per [`Config::collapse`]
+    Collapsed,
+    /// Components replaced by the marker of [`Config::elision`]
+    Elided,
+}
+
+impl<'l> ShortenedLine<'l> {
+    /// Shortens `line` as per `cfg`.
+    pub fn new(cfg: &Config, line: &'l str) -> Self {
+        ShortenedLine {
+            segments: shorten_components(cfg, line).segments().collect(),
+            delimiter: cfg.joining_delimiter().map(String::from),
+        }
+    }
+
+    /// Joins the segments with `delimiter` when rendered.
+    pub fn with_delimiter(mut self, delimiter: impl Into<String>) -> Self {
+        self.delimiter = Some(delimiter.into());
+        self
+    }
+
+    /// Joins the segments with the delimiters that followed them in the line when rendered.
+    pub fn with_original_delimiters(mut self) -> Self {
+        self.delimiter = None;
+        self
+    }
+
+    /// The segments of the line, in order.
+    pub fn segments(&self) -> &[Segment<'l>] {
+        &self.segments
+    }
+
+    /// Consumes the line, and returns its segments.
+    pub fn into_segments(self) -> Vec<Segment<'l>> {
+        self.segments
+    }
+}
+
+impl fmt::Display for ShortenedLine<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for segment in &self.segments {
+            f.write_str(&segment.output)?;
+            if let Some(delimiter) = segment.delimiter {
+                f.write_str(self.delimiter.as_deref().unwrap_or(delimiter))?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Alias, Collapse, Delimiter, Elision, EmptyComponents, Prefix, Regex};
+    use alloc::format;
+    use alloc::string::ToString;
+    use alloc::vec;
+    use insta::assert_yaml_snapshot;
+
+    fn describe(line: &ShortenedLine<'_>) -> Vec<String> {
+        line.segments()
+            .iter()
+            .map(|segment| {
+                format!(
+                    "{:?} -> {:?} at {:?} ({:?})",
+                    segment.original, segment.output, segment.span, segment.reason
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn shortened_line_records_why_components_are_kept_or_shortened() {
+        // GIVEN
+        let cfg = Config {
+            ignore_first_n: 1,
+            ignore_regex: Some(Regex::new("^billing$").unwrap()),
+            ignore_indices: Some("-2".parse().unwrap()),
+            rules: vec!["word:test => keep".parse().unwrap()],
+            ..Config::default()
+        };
+
+        // WHEN
+        let result = ShortenedLine::new(&cfg, "src/main//test/billing/api/utils/Server.scala");
+
+        // THEN
+        assert_yaml_snapshot!(describe(&result), @r#"
+        - "\"src\" -> \"src\" at 0..3 (IgnoredFirstN)"
+        - "\"main\" -> \"m\" at 4..8 (Shortened)"
+        - "\"\" -> \"\" at 9..9 (Empty)"
+        - "\"test\" -> \"test\" at 10..14 (Rule(0))"
+        - "\"billing\" -> \"billing\" at 15..22 (MatchedIgnoreRegex)"
+        - "\"api\" -> \"a\" at 23..26 (Shortened)"
+        - "\"utils\" -> \"utils\" at 27..32 (IgnoredIndex)"
+        - "\"Server.scala\" -> \"Server.scala\" at 33..45 (IgnoredLastN)"
+        "#);
+        assert_eq!(
+            result.to_string(),
+            "src/m//test/billing/a/utils/Server.scala"
+        );
+    }
+
+    #[test]
+    fn shortened_line_spans_substituted_and_merged_components() {
+        // GIVEN
+        let cfg = Config {
+            delimiter: Delimiter::from(vec!["/", "::"]),
+            empty_components: EmptyComponents::Drop,
+            prefixes: vec![Prefix::new("/home/alice", "~")],
+            aliases: vec![Alias::new(["src", "main"], "main")],
+            collapse: Collapse::Runs,
+            elision: Some(Elision {
+                keep_first: 4,
+                keep_last: 2,
+                ..Elision::default()
+            }),
+            ..Config::default()
+        };
+
+        // WHEN
+        let result = ShortenedLine::new(
+            &cfg,
+            "/home/alice/src/main//lib/lib::core/utils/api/web/Server.scala",
+        );
+
+        // THEN
+        assert_yaml_snapshot!(describe(&result), @r#"
+        - "\"/home/alice\" -> \"~\" at 0..11 (Prefix)"
+        - "\"src/main\" -> \"main\" at 12..20 (Alias)"
+        - "\"lib/lib\" -> \"l×2\" at 22..29 (Collapsed)"
+        - "\"core/utils/api\" -> \"…\" at 31..45 (Elided)"
+        - "\"web\" -> \"w\" at 46..49 (Shortened)"
+        - "\"Server.scala\" -> \"Server.scala\" at 50..62 (IgnoredLastN)"
+        "#);
+        assert_eq!(result.to_string(), "~/main/l×2/…/w/Server.scala");
+    }
+
+    #[test]
+    fn shortened_line_records_the_root_and_components_within_max_width() {
+        // GIVEN
+        let cfg = Config {
+            absolute_root: true,
+            max_width: Some(20),
+            ..Config::default()
+        };
+
+        // WHEN
+        let result = ShortenedLine::new(&cfg, "/module/submodule/lib.rs");
+
+        // THEN
+        assert_yaml_snapshot!(describe(&result), @r#"
+        - "\"\" -> \"\" at 0..0 (Root)"
+        - "\"module\" -> \"m\" at 1..7 (Shortened)"
+        - "\"submodule\" -> \"submodule\" at 8..17 (FitsMaxWidth)"
+        - "\"lib.rs\" -> \"lib.rs\" at 18..24 (IgnoredLastN)"
+        "#);
+    }
+
+    #[test]
+    fn shortened_line_can_be_joined_with_any_delimiter() {
+        // GIVEN
+        let cfg = Config {
+            delimiter: Delimiter::from(vec!["/", "::"]),
+            keep_delimiters: true,
+            ..Config::default()
+        };
+        let line = ShortenedLine::new(&cfg, "module/service::lib.rs");
+
+        // WHEN
+        let kept = line.to_string();
+        let replaced = line.clone().with_delimiter(" > ").to_string();
+        let restored = line
+            .with_delimiter(".")
+            .with_original_delimiters()
+            .to_string();
+
+        // THEN
+        assert_eq!(kept, "m/s::lib.rs");
+        assert_eq!(replaced, "m > s > lib.rs");
+        assert_eq!(restored, "m/s::lib.rs");
+    }
+}
@@ -0,0 +1,266 @@
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt;
+use core::str::FromStr;
+
+/// Chooses components by their indices, using Python-like index and slice syntax.
+///
+/// A selector is a comma separated list of indices (`2`) and slices (`start:stop` or
+/// `start:stop:step`, where any part can be left out); negative indices count from the end (`-1` is
+/// the last component). A component is chosen if any of the items selects it; indices out of
+/// range select nothing, and slices are clamped to the line, as they are in Python.
+///
+/// Example:
+/// ```
+/// use squidge::{Config, Selector, shorten_line};
+///
+/// let selector: Selector = "2,-2:".parse().unwrap();
+/// assert!(selector.contains(2, 6));
+/// assert!(!selector.contains(3, 6));
+///
+/// let cfg = Config {
+///     ignore_indices: Some(selector),
+///     ..Config::default()
+/// };
+/// let result = shorten_line(&cfg, "src/main/billing/api/utils/Server.scala");
+/// assert_eq!(result, vec!["s", "m", "billing", "a", "utils", "Server.scala"]);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Selector {
+    items: Vec<Item>,
+}
+
+/// An index, or a slice of indices, in a [`Selector`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Item {
+    Index(isize),
+    Slice {
+        start: Option<isize>,
+        stop: Option<isize>,
+        step: isize,
+    },
+}
+
+impl Selector {
+    /// Whether the component at index `i` (out of `num_components`) is chosen.
+    pub fn contains(&self, i: usize, num_components: usize) -> bool {
+        self.items
+            .iter()
+            .any(|item| item.contains(i, num_components))
+    }
+}
+
+impl Item {
+    fn contains(&self, i: usize, num_components: usize) -> bool {
+        let (Ok(i), Ok(len)) = (isize::try_from(i), isize::try_from(num_components)) else {
+            return false;
+        };
+        if i >= len {
+            return false;
+        }
+
+        match *self {
+            Item::Index(index) if index < 0 => index + len == i,
+            Item::Index(index) => index == i,
+            Item::Slice { start, stop, step } if step > 0 => {
+                let start = clamp(start, len, 0, len).unwrap_or(0);
+                let stop = clamp(stop, len, 0, len).unwrap_or(len);
+                (start..stop).contains(&i) && (i - start) % step == 0
+            }
+            Item::Slice { start, stop, step } => {
+                let start = clamp(start, len, -1, len - 1).unwrap_or(len - 1);
+                let stop = clamp(stop, len, -1, len - 1).unwrap_or(-1);
+                stop < i
+                    && i <= start
+                    && (start - i)
+                        .unsigned_abs()
+                        .is_multiple_of(step.unsigned_abs())
+            }
+        }
+    }
+}
+
+/// Resolves a (possibly negative) slice bound against a line with `len` components, and clamps it
+/// to `min..=max`.
+fn clamp(bound: Option<isize>, len: isize, min: isize, max: isize) -> Option<isize> {
+    bound.map(|b| (if b < 0 { b + len } else { b }).clamp(min, max))
+}
+
+impl fmt::Display for Selector {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, item) in self.items.iter().enumerate() {
+            if i > 0 {
+                write!(f, ",")?;
+            }
+            write!(f, "{item}")?;
+        }
+
+        Ok(())
+    }
+}
+
+impl fmt::Display for Item {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Item::Index(index) => write!(f, "{index}"),
+            Item::Slice { start, stop, step } => {
+                if let Some(start) = start {
+                    write!(f, "{start}")?;
+                }
+                write!(f, ":")?;
+                if let Some(stop) = stop {
+                    write!(f, "{stop}")?;
+                }
+                if *step != 1 {
+                    write!(f, ":{step}")?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Error returned when parsing a [`Selector`] from a string fails.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseSelectorError(String);
+
+impl fmt::Display for ParseSelectorError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl core::error::Error for ParseSelectorError {}
+
+impl FromStr for Selector {
+    type Err = ParseSelectorError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let items = s
+            .split(',')
+            .map(|item| item.trim().parse())
+            .collect::<Result<_, _>>()?;
+
+        Ok(Selector { items })
+    }
+}
+
+impl FromStr for Item {
+    type Err = ParseSelectorError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let bound = |b: &str| match b.trim() {
+            "" => Ok(None),
+            b => b
+                .parse()
+                .map(Some)
+                .map_err(|_| ParseSelectorError(format!(r#"invalid index "{b}""#))),
+        };
+
+        match s.split(':').collect::<Vec<_>>()[..] {
+            [""] => Err(ParseSelectorError(
+                "selector items cannot be empty".to_string(),
+            )),
+            [index] => Ok(Item::Index(bound(index)?.unwrap_or_default())),
+            [start, stop] => Ok(Item::Slice {
+                start: bound(start)?,
+                stop: bound(stop)?,
+                step: 1,
+            }),
+            [start, stop, step] => match bound(step)? {
+                Some(0) => Err(ParseSelectorError(format!(
+                    r#"step cannot be zero in slice "{s}""#
+                ))),
+                step => Ok(Item::Slice {
+                    start: bound(start)?,
+                    stop: bound(stop)?,
+                    step: step.unwrap_or(1),
+                }),
+            },
+            _ => Err(ParseSelectorError(format!(r#"invalid slice "{s}""#))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+    use insta::assert_yaml_snapshot;
+
+    fn select(selector: &str, num_components: usize) -> Vec<usize> {
+        let selector: Selector = selector.parse().unwrap();
+        (0..num_components)
+            .filter(|&i| selector.contains(i, num_components))
+            .collect()
+    }
+
+    #[test]
+    fn selector_matches_python_indexing() {
+        // GIVEN
+        let selectors = [
+            "2,-2:", "0", "-1", "9", "-9", "1:-1", ":2", "-3:", "::2", "1::3", "::-1", "-2::-2",
+            "4:1:-1", "-9:9",
+        ];
+
+        // WHEN
+        let result: Vec<_> = selectors
+            .iter()
+            .map(|selector| format!("{selector} -> {:?}", select(selector, 6)))
+            .collect();
+
+        // THEN
+        assert_yaml_snapshot!(result, @r#"
+        - "2,-2: -> [2, 4, 5]"
+        - "0 -> [0]"
+        - "-1 -> [5]"
+        - "9 -> []"
+        - "-9 -> []"
+        - "1:-1 -> [1, 2, 3, 4]"
+        - ":2 -> [0, 1]"
+        - "-3: -> [3, 4, 5]"
+        - "::2 -> [0, 2, 4]"
+        - "1::3 -> [1, 4]"
+        - "::-1 -> [0, 1, 2, 3, 4, 5]"
+        - "-2::-2 -> [0, 2, 4]"
+        - "4:1:-1 -> [2, 3, 4]"
+        - "-9:9 -> [0, 1, 2, 3, 4, 5]"
+        "#);
+    }
+
+    #[test]
+    fn selector_handles_the_most_negative_step() {
+        // GIVEN
+        // WHEN
+        let result = select("::-9223372036854775808", 6);
+
+        // THEN
+        assert_eq!(result, vec![5]);
+    }
+
+    #[test]
+    fn parsing_selectors_works() {
+        // GIVEN
+        // WHEN
+        let parsed: Vec<_> = ["2, -2:", "1:4:1", "::-1", ":"]
+            .into_iter()
+            .map(|s| s.parse::<Selector>().unwrap().to_string())
+            .collect();
+        let errs: Vec<_> = ["", "1,,2", "a", "1:b", "::0", "1:2:3:4"]
+            .into_iter()
+            .map(|s| s.parse::<Selector>().unwrap_err().to_string())
+            .collect();
+
+        // THEN
+        assert_eq!(parsed, vec!["2,-2:", "1:4", "::-1", ":"]);
+        assert_yaml_snapshot!(errs, @r#"
+        - selector items cannot be empty
+        - selector items cannot be empty
+        - "invalid index \"a\""
+        - "invalid index \"b\""
+        - "step cannot be zero in slice \"::0\""
+        - "invalid slice \"1:2:3:4\""
+        "#);
+    }
+}
@@ -0,0 +1,513 @@
+use crate::builder::{validate, validate_delimiter};
+use crate::{
+    Action, Collapse, Config, Delimiter, EmptyComponents, Engine, Regex, Rule, Selector, Strategy,
+};
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use serde::de::Error as _;
+use serde::ser::Error as _;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// Configs are (de)serialized via their derived impls; deserialized configs are validated the
+/// way [`ConfigBuilder::build`](crate::ConfigBuilder::build) validates them.
+impl Serialize for Config {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        Config::serialize(self, serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Config {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let cfg = Config::deserialize(deserializer)?;
+        validate(&cfg).map_err(D::Error::custom)?;
+
+        Ok(cfg)
+    }
+}
+
+/// Delimiters are represented as a string (literal), a list of strings (set), or a map with a
+/// `regex` key (and an `engine` key, if the regex wasn't compiled with the default engine);
+/// deserialized delimiters are validated the way the builder validates them.
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum DelimiterRepr {
+    Literal(String),
+    Set(Vec<String>),
+    Regex {
+        regex: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        engine: Option<Engine>,
+    },
+}
+
+impl Serialize for Delimiter {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Delimiter::Literal(literal) => DelimiterRepr::Literal(literal.clone()),
+            Delimiter::Set(literals) => DelimiterRepr::Set(literals.clone()),
+            Delimiter::Regex(re) => DelimiterRepr::Regex {
+                regex: re.as_str().to_string(),
+                engine: non_default(re.engine()),
+            },
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Delimiter {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let delimiter = match DelimiterRepr::deserialize(deserializer)? {
+            DelimiterRepr::Literal(literal) => Delimiter::Literal(literal),
+            DelimiterRepr::Set(literals) => Delimiter::Set(literals),
+            DelimiterRepr::Regex { regex, engine } => {
+                compile(&regex, engine.unwrap_or_default()).map(Delimiter::Regex)?
+            }
+        };
+        validate_delimiter(&delimiter).map_err(D::Error::custom)?;
+
+        Ok(delimiter)
+    }
+}
+
+/// Engines are represented by their names (eg, `fancy-regex`).
+impl Serialize for Engine {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Engine {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(D::Error::custom)
+    }
+}
+
+/// Strategies are represented by their textual form (eg, `first:2`); custom strategies can't be
+/// serialized.
+impl Serialize for Strategy {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Strategy::Custom(_) => Err(S::Error::custom("custom strategies cannot be serialized")),
+            _ => serializer.collect_str(self),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Strategy {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(D::Error::custom)
+    }
+}
+
+/// Collapse modes are represented by their textual form (eg, `runs`).
+impl Serialize for Collapse {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Collapse {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(D::Error::custom)
+    }
+}
+
+/// Empty component policies are represented by their textual form (eg, `placeholder:_`).
+impl Serialize for EmptyComponents {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for EmptyComponents {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(D::Error::custom)
+    }
+}
+
+/// Selectors are represented by their textual form (eg, `2,-2:`).
+impl Serialize for Selector {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Selector {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(D::Error::custom)
+    }
+}
+
+/// Rules are represented by their textual form (eg, `word:main => keep`); rules using custom
+/// strategies can't be serialized.
+impl Serialize for Rule {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self.action {
+            Action::Strategy(Strategy::Custom(_)) => {
+                Err(S::Error::custom("custom strategies cannot be serialized"))
+            }
+            _ => serializer.collect_str(self),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Rule {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(D::Error::custom)
+    }
+}
+
+/// Regexes are represented by their patterns, or, if they weren't compiled with the default
+/// engine, by a map with `pattern` and `engine` keys.
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum RegexRepr {
+    Pattern(String),
+    WithEngine { pattern: String, engine: Engine },
+}
+
+impl From<&Regex> for RegexRepr {
+    fn from(regex: &Regex) -> Self {
+        let pattern = regex.as_str().to_string();
+        match non_default(regex.engine()) {
+            Some(engine) => RegexRepr::WithEngine { pattern, engine },
+            None => RegexRepr::Pattern(pattern),
+        }
+    }
+}
+
+impl RegexRepr {
+    fn compile<E: serde::de::Error>(self) -> Result<Regex, E> {
+        match self {
+            RegexRepr::Pattern(pattern) => compile(&pattern, Engine::default()),
+            RegexRepr::WithEngine { pattern, engine } => compile(&pattern, engine),
+        }
+    }
+}
+
+/// (De)serializes a regex as its pattern (along with its engine, if it isn't the default one).
+pub(crate) mod required_regex {
+    use super::*;
+
+    pub(crate) fn serialize<S: Serializer>(
+        regex: &Regex,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        RegexRepr::from(regex).serialize(serializer)
+    }
+
+    pub(crate) fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Regex, D::Error> {
+        RegexRepr::deserialize(deserializer)?.compile()
+    }
+}
+
+/// (De)serializes an optional regex as its pattern (along with its engine, if it isn't the
+/// default one).
+pub(crate) mod optional_regex {
+    use super::*;
+
+    pub(crate) fn serialize<S: Serializer>(
+        regex: &Option<Regex>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        regex.as_ref().map(RegexRepr::from).serialize(serializer)
+    }
+
+    pub(crate) fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<Regex>, D::Error> {
+        Option::<RegexRepr>::deserialize(deserializer)?
+            .map(RegexRepr::compile)
+            .transpose()
+    }
+}
+
+/// Returns `engine`, unless it's the default one, which is left out of serialized regexes.
+fn non_default(engine: Engine) -> Option<Engine> {
+    (engine != Engine::default()).then_some(engine)
+}
+
+fn compile<E: serde::de::Error>(pattern: &str, engine: Engine) -> Result<Regex, E> {
+    Regex::with_engine(pattern, engine)
+        .map_err(|e| E::custom(format!("couldn't compile regex \"{pattern}\": {e}")))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        Alias, Collapse, Config, Delimiter, Elision, EmptyComponents, Extraction, Prefix, Quoting,
+        Regex, Strategy,
+    };
+    use alloc::string::{String, ToString};
+    use alloc::vec;
+    use alloc::vec::Vec;
+    use insta::{assert_snapshot, assert_yaml_snapshot};
+
+    #[derive(Debug)]
+    struct Reverse;
+
+    impl crate::Abbreviator for Reverse {
+        fn abbreviate<'c>(&self, component: &'c str) -> alloc::borrow::Cow<'c, str> {
+            component.chars().rev().collect::<String>().into()
+        }
+    }
+
+    #[test]
+    fn config_serializes_regexes_as_patterns() {
+        // GIVEN
+        let cfg = Config {
+            delimiter: Delimiter::Regex(Regex::new(r"\s*->\s*").unwrap()),
+            ignore_regex: Some(Regex::new("billing|utils").unwrap()),
+            ignore_indices: Some("2,-3::-1".parse().unwrap()),
+            rules: vec!["word:src => drop".parse().unwrap()],
+            extraction: Some(Extraction {
+                regex: Regex::new(r"service-(\w)\w*-(v\d+)").unwrap(),
+                replacement: "$1$2".to_string(),
+            }),
+            strategy: Strategy::FirstChars(2),
+            ..Config::default()
+        };
+
+        // WHEN
+        // THEN
+        assert_yaml_snapshot!(cfg, @r#"
+        delimiter:
+          regex: "\\s*->\\s*"
+        output_delimiter: /
+        keep_delimiters: false
+        quoting: ~
+        ignore_first_n: 0
+        ignore_last_n: 1
+        ignore_regex: billing|utils
+        ignore_indices: "2,-3::-1"
+        empty_components: keep
+        absolute_root: false
+        rules:
+          - "word:src => drop"
+        prefixes: []
+        aliases: []
+        extraction:
+          regex: "service-(\\w)\\w*-(v\\d+)"
+          replacement: $1$2
+        strategy: "first:2"
+        filename_aware: false
+        collapse: "off"
+        elision: ~
+        max_width: ~
+        "#);
+    }
+
+    #[test]
+    fn config_round_trips() {
+        // GIVEN
+        let configs = [
+            Config::default(),
+            Config {
+                delimiter: Delimiter::from(vec!["/", "::"]),
+                output_delimiter: " > ".to_string(),
+                quoting: Some(Quoting {
+                    quote: '\'',
+                    escape: '\\',
+                }),
+                ignore_regex: Some(Regex::new("^sub").unwrap()),
+                empty_components: EmptyComponents::Placeholder("_".to_string()),
+                absolute_root: true,
+                rules: vec![
+                    "regex:^(billing|utils)$ => keep".parse().unwrap(),
+                    "position:-2 => strategy:last:2".parse().unwrap(),
+                ],
+                prefixes: vec![Prefix::new("/home/alice", "~")],
+                aliases: vec![Alias::new(["src", "main", "scala"], "main")],
+                strategy: Strategy::FirstAndLast,
+                collapse: Collapse::Blocks,
+                elision: Some(Elision {
+                    keep_first: 2,
+                    keep_last: 1,
+                    marker: "…({count})".to_string(),
+                }),
+                max_width: Some(40),
+                ..Config::default()
+            },
+            Config {
+                delimiter: Delimiter::from(Regex::new("[/.]").unwrap()),
+                keep_delimiters: true,
+                ignore_first_n: 2,
+                ignore_last_n: 0,
+                ..Config::default()
+            },
+        ];
+
+        for cfg in configs {
+            // WHEN
+            let json = serde_json::to_string(&cfg).unwrap();
+            let result: Config = serde_json::from_str(&json).unwrap();
+
+            // THEN
+            assert_eq!(result, cfg);
+        }
+    }
+
+    #[cfg(all(feature = "regex", feature = "fancy-regex"))]
+    #[test]
+    fn config_round_trips_regexes_with_their_engines() {
+        // GIVEN
+        let fancy = |pattern| Regex::with_engine(pattern, crate::Engine::FancyRegex).unwrap();
+        let cfg = Config {
+            delimiter: Delimiter::Regex(fancy(r"/(?=\w)")),
+            ignore_regex: Some(fancy("^test(?!utils)")),
+            rules: vec!["fancy-regex:^(\\w)\\1$ => keep".parse().unwrap()],
+            extraction: Some(Extraction {
+                regex: fancy(r"^(?<name>\w+)(?<!-v\d)$"),
+                replacement: "${name}".to_string(),
+            }),
+            ..Config::default()
+        };
+
+        // WHEN
+        let json = serde_json::to_string(&cfg).unwrap();
+        let result: Config = serde_json::from_str(&json).unwrap();
+
+        // THEN
+        assert_eq!(result, cfg);
+        assert_ne!(
+            Config {
+                ignore_regex: Some(fancy("^test")),
+                ..Config::default()
+            },
+            Config {
+                ignore_regex: Some(Regex::new("^test").unwrap()),
+                ..Config::default()
+            }
+        );
+        assert_yaml_snapshot!(cfg, @r#"
+        delimiter:
+          regex: "/(?=\\w)"
+          engine: fancy-regex
+        output_delimiter: /
+        keep_delimiters: false
+        quoting: ~
+        ignore_first_n: 0
+        ignore_last_n: 1
+        ignore_regex:
+          pattern: ^test(?!utils)
+          engine: fancy-regex
+        ignore_indices: ~
+        empty_components: keep
+        absolute_root: false
+        rules:
+          - "fancy-regex:^(\\w)\\1$ => keep"
+        prefixes: []
+        aliases: []
+        extraction:
+          regex:
+            pattern: "^(?<name>\\w+)(?<!-v\\d)$"
+            engine: fancy-regex
+          replacement: "${name}"
+        strategy: first
+        filename_aware: false
+        collapse: "off"
+        elision: ~
+        max_width: ~
+        "#);
+    }
+
+    #[test]
+    fn config_deserialization_falls_back_to_defaults() {
+        // GIVEN
+        let json = r#"{"delimiter": ["/", "."], "strategy": "last:2"}"#;
+
+        // WHEN
+        let result: Config = serde_json::from_str(json).unwrap();
+
+        // THEN
+        assert_eq!(
+            result,
+            Config {
+                delimiter: Delimiter::from(vec!["/", "."]),
+                strategy: Strategy::LastChars(2),
+                ..Config::default()
+            }
+        );
+    }
+
+    #[test]
+    fn config_deserialization_fails_for_invalid_values() {
+        // GIVEN
+        // WHEN
+        let regex_err = serde_json::from_str::<Config>(r#"{"ignore_regex": "(module"}"#)
+            .unwrap_err()
+            .to_string();
+        let strategy_err = serde_json::from_str::<Config>(r#"{"strategy": "middle"}"#)
+            .unwrap_err()
+            .to_string();
+
+        // THEN
+        assert!(regex_err.starts_with(r#"couldn't compile regex "(module""#));
+        assert_snapshot!(strategy_err, @r#"invalid strategy "middle" (possible values: first, first:N, last, last:N, first-last) at line 1 column 22"#);
+    }
+
+    #[test]
+    fn config_deserialization_validates_values() {
+        // GIVEN
+        let configs = [
+            r#"{"delimiter": ""}"#,
+            r#"{"delimiter": ["/", ""]}"#,
+            r#"{"delimiter": []}"#,
+            r#"{"delimiter": {"regex": "/*"}}"#,
+            r#"{"strategy": "first:0"}"#,
+            r#"{"rules": ["word:main => strategy:last:0"]}"#,
+            r#"{"max_width": 0}"#,
+        ];
+
+        // WHEN
+        let errs: Vec<_> = configs
+            .into_iter()
+            .map(|json| {
+                serde_json::from_str::<Config>(json)
+                    .unwrap_err()
+                    .to_string()
+            })
+            .collect();
+
+        // THEN
+        assert_yaml_snapshot!(errs, @r#"
+        - delimiter cannot be empty at line 1 column 17
+        - delimiter cannot be empty at line 1 column 24
+        - at least one delimiter needs to be provided at line 1 column 17
+        - "delimiter regex \"/*\" matches the empty string at line 1 column 30"
+        - "strategy \"first:0\" would abbreviate components to nothing"
+        - "strategy \"last:0\" would abbreviate components to nothing"
+        - max width needs to be greater than 0
+        "#);
+    }
+
+    #[test]
+    fn custom_strategies_cannot_be_serialized() {
+        // GIVEN
+        let cfg = Config {
+            strategy: Strategy::custom(Reverse),
+            ..Config::default()
+        };
+
+        // WHEN
+        let err = serde_json::to_string(&cfg).unwrap_err();
+
+        // THEN
+        assert_snapshot!(err, @"custom strategies cannot be serialized");
+    }
+}
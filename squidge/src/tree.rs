@@ -0,0 +1,341 @@
+use crate::Config;
+use crate::alias::Part;
+use alloc::borrow::Cow;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt;
+
+/// The components of several lines, arranged as a tree.
+///
+/// Lines that start with the same components share the nodes for them; children are kept in the
+/// order they're first seen in. Interior nodes are shortened as per a [`Config`] (its
+/// [`collapse`](Config::collapse), [`elision`](Config::elision) and
+/// [`max_width`](Config::max_width) aside), while leaves are kept whole. Dropped components are
+/// left out of the tree, prefix and alias tokens are single nodes, and the preserved root of
+/// absolute lines (see [`Config::absolute_root`]) is a node named after its delimiter.
+///
+/// A tree renders (via [`fmt::Display`]) with box-drawing characters, like `tree` does, and can
+/// also be rendered as a Graphviz graph via [`Tree::to_dot`].
+///
+/// Example:
+/// ```
+/// use squidge::{Config, Tree};
+///
+/// let lines = [
+///     "src/main/scala/billing/Server.scala",
+///     "src/main/scala/billing/Components.scala",
+///     "src/test/scala/billing/ServerSpec.scala",
+/// ];
+/// let tree = Tree::new(&Config::default(), lines);
+/// assert_eq!(
+///     tree.to_string(),
+///     "\
+/// s
+/// ├── m
+/// │   └── s
+/// │       └── b
+/// │           ├── Server.scala
+/// │           └── Components.scala
+/// └── t
+///     └── s
+///         └── b
+///             └── ServerSpec.scala"
+/// );
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Tree {
+    roots: Vec<Node>,
+}
+
+/// A component in a [`Tree`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Node {
+    name: String,
+    shortened: String,
+    line: usize,
+    children: Vec<Node>,
+}
+
+impl Tree {
+    /// Builds a tree out of the components of `lines`, shortened as per `cfg`.
+    pub fn new<I>(cfg: &Config, lines: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: AsRef<str>,
+    {
+        let mut tree = Tree::default();
+
+        for (index, line) in lines.into_iter().enumerate() {
+            let parts = cfg.parts(line.as_ref());
+            let root = parts.root();
+            let parts: Vec<_> = parts.map(|(part, _)| part).collect();
+            let num_elements = parts.len().saturating_sub(1);
+
+            let mut nodes = &mut tree.roots;
+            if let Some(root) = root {
+                nodes = &mut Node::find_or_insert(nodes, root, Cow::Borrowed(root), index).children;
+            }
+            for (i, part) in parts.into_iter().enumerate() {
+                let name = match &part {
+                    Part::Component(component) => component.clone(),
+                    Part::Alias(token) => Cow::Borrowed(*token),
+                };
+                let Some((shortened, _)) = cfg.shorten_part(i, num_elements, part, true, None)
+                else {
+                    continue;
+                };
+                nodes = &mut Node::find_or_insert(nodes, &name, shortened, index).children;
+            }
+        }
+
+        tree
+    }
+
+    /// The top-level nodes of the tree.
+    pub fn roots(&self) -> &[Node] {
+        &self.roots
+    }
+
+    /// Renders the tree as a Graphviz (DOT) directed graph.
+    ///
+    /// Example:
+    /// ```
+    /// use squidge::{Config, Tree};
+    ///
+    /// let tree = Tree::new(&Config::default(), ["src/lib.rs", "src/main.rs"]);
+    /// assert_eq!(
+    ///     tree.to_dot(),
+    ///     r#"digraph {
+    ///     n0 [label="s"];
+    ///     n1 [label="lib.rs"];
+    ///     n0 -> n1;
+    ///     n2 [label="main.rs"];
+    ///     n0 -> n2;
+    /// }"#
+    /// );
+    /// ```
+    pub fn to_dot(&self) -> String {
+        let mut out = String::from("digraph {\n");
+        let mut next_id = 0;
+        for root in &self.roots {
+            root.write_dot(&mut out, &mut next_id);
+        }
+        out.push('}');
+
+        out
+    }
+}
+
+impl Node {
+    /// Returns the node named `name` among `nodes`, adding it (as coming from the line at index
+    /// `line`) if there's none.
+    fn find_or_insert<'n>(
+        nodes: &'n mut Vec<Node>,
+        name: &str,
+        shortened: Cow<'_, str>,
+        line: usize,
+    ) -> &'n mut Node {
+        let index = match nodes.iter().position(|node| node.name == name) {
+            Some(index) => index,
+            None => {
+                nodes.push(Node {
+                    name: name.to_string(),
+                    shortened: shortened.into_owned(),
+                    line,
+                    children: vec![],
+                });
+                nodes.len() - 1
+            }
+        };
+
+        &mut nodes[index]
+    }
+
+    /// The component the node stands for.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// What the node is rendered as: the shortened component for interior nodes, and the whole
+    /// component for leaves.
+    pub fn label(&self) -> &str {
+        if self.children.is_empty() {
+            &self.name
+        } else {
+            &self.shortened
+        }
+    }
+
+    /// The index of the first of the lines the tree was built from that the node's component is
+    /// in.
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    /// The nodes for the components that follow this one.
+    pub fn children(&self) -> &[Node] {
+        &self.children
+    }
+
+    fn fmt_children(&self, f: &mut fmt::Formatter<'_>, indent: &str) -> fmt::Result {
+        for (i, child) in self.children.iter().enumerate() {
+            let (branch, continuation) = if i == self.children.len() - 1 {
+                ("└── ", "    ")
+            } else {
+                ("├── ", "│   ")
+            };
+            write!(f, "\n{indent}{branch}{}", child.label())?;
+            child.fmt_children(f, &format!("{indent}{continuation}"))?;
+        }
+
+        Ok(())
+    }
+
+    /// Writes the node and its descendants, returning the node's id.
+    fn write_dot(&self, out: &mut String, next_id: &mut usize) -> usize {
+        let id = *next_id;
+        *next_id += 1;
+
+        let label = self.label().replace('\\', r"\\").replace('"', r#"\""#);
+        out.push_str(&format!("    n{id} [label=\"{label}\"];\n"));
+        for child in &self.children {
+            let child_id = child.write_dot(out, next_id);
+            out.push_str(&format!("    n{id} -> n{child_id};\n"));
+        }
+
+        id
+    }
+}
+
+impl fmt::Display for Tree {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, root) in self.roots.iter().enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            write!(f, "{}", root.label())?;
+            root.fmt_children(f, "")?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Alias, EmptyComponents};
+    use insta::assert_snapshot;
+
+    #[test]
+    fn tree_keeps_leaves_whole_and_shortens_interior_nodes() {
+        // GIVEN
+        let lines = [
+            "src/main/scala/admin/billing/Server.scala",
+            "src/main/scala/admin/billing",
+            "src/main/scala/admin/backup/Server.scala",
+            "src/main/scala/admin/backup/Components.scala",
+            "docs/README.md",
+        ];
+        let cfg = Config {
+            rules: vec!["word:admin => drop".parse().unwrap()],
+            aliases: vec![Alias::new(["src", "main", "scala"], "main")],
+            ..Config::default()
+        };
+
+        // WHEN
+        let result = Tree::new(&cfg, lines);
+
+        // THEN
+        assert_snapshot!(result, @r"
+        main
+        ├── b
+        │   └── Server.scala
+        └── b
+            ├── Server.scala
+            └── Components.scala
+        d
+        └── README.md
+        ");
+    }
+
+    #[test]
+    fn tree_renders_as_dot_with_escaped_labels() {
+        // GIVEN
+        let lines = [r#"a"b/c/e\f"#, r#"a"b/g"#];
+        let cfg = Config {
+            ignore_regex: Some(crate::Regex::new("^a").unwrap()),
+            ..Config::default()
+        };
+
+        // WHEN
+        let result = Tree::new(&cfg, lines).to_dot();
+
+        // THEN
+        assert_snapshot!(result, @r#"
+        digraph {
+            n0 [label="a\"b"];
+            n1 [label="c"];
+            n2 [label="e\\f"];
+            n1 -> n2;
+            n0 -> n1;
+            n3 [label="g"];
+            n0 -> n3;
+        }
+        "#);
+    }
+
+    #[test]
+    fn tree_nodes_know_the_first_line_they_come_from() {
+        // GIVEN
+        let lines = ["src/lib.rs", "docs/README.md", "src/main.rs"];
+
+        // WHEN
+        let tree = Tree::new(&Config::default(), lines);
+
+        // THEN
+        let result: Vec<_> = tree
+            .roots()
+            .iter()
+            .flat_map(|root| core::iter::once(root).chain(root.children()))
+            .map(|node| (node.name(), node.line()))
+            .collect();
+        assert_eq!(
+            result,
+            vec![
+                ("src", 0),
+                ("lib.rs", 0),
+                ("main.rs", 2),
+                ("docs", 1),
+                ("README.md", 1)
+            ]
+        );
+    }
+
+    #[test]
+    fn tree_has_a_node_for_the_absolute_root() {
+        // GIVEN
+        let lines = ["/usr/local/bin", "/usr//lib/", "usr/lib"];
+        let cfg = Config {
+            empty_components: EmptyComponents::Drop,
+            absolute_root: true,
+            ..Config::default()
+        };
+
+        // WHEN
+        let result = Tree::new(&cfg, lines);
+
+        // THEN
+        assert_snapshot!(result, @r"
+        /
+        └── u
+            ├── l
+            │   └── bin
+            └── lib
+        u
+        └── lib
+        ");
+    }
+}
@@ -0,0 +1,502 @@
+use crate::alias::Part;
+use crate::{Config, filename, shorten_components_with, units};
+use alloc::borrow::Cow;
+use alloc::collections::{BTreeMap, BTreeSet};
+use alloc::string::String;
+use alloc::vec::Vec;
+
+/// Shortens several lines at once, abbreviating each component to the shortest prefix that is
+/// unique among its siblings, ie, the components that share the same parent across all lines.
+///
+/// This is similar to how fish's `prompt_pwd` or zsh's `%~` completion disambiguate paths.
+/// Components that [`Config`] leaves untouched (via `ignore_first_n`, `ignore_last_n`,
+/// `ignore_regex` or [`Config::rules`]) are kept as is, the other actions of rules apply as usual,
+/// and [`Config::max_width`] is honoured per line. Components matching [`Config::extraction`] are
+/// abbreviated with it; [`Config::strategy`] is not used in this mode.
+///
+/// Example:
+/// ```
+/// use squidge::{Config, shorten_lines_unique};
+///
+/// let lines = ["admin/billing/Server.scala", "admin/backup/Server.scala"];
+/// let result = shorten_lines_unique(&Config::default(), &lines);
+/// assert_eq!(
+///     result,
+///     vec![
+///         vec!["a", "bi", "Server.scala"],
+///         vec!["a", "ba", "Server.scala"],
+///     ]
+/// );
+/// ```
+pub fn shorten_lines_unique<'l, L: AsRef<str>>(
+    cfg: &'l Config,
+    lines: &'l [L],
+) -> Vec<Vec<String>> {
+    let split_lines: Vec<(Option<&str>, Vec<Part>)> = lines
+        .iter()
+        .map(|l| {
+            let parts = cfg.parts(l.as_ref());
+            (parts.root(), parts.map(|(part, _)| part).collect())
+        })
+        .collect();
+
+    // siblings are the components that share the same root and parts before them
+    let mut siblings: BTreeMap<(Option<&str>, &[Part]), BTreeSet<&str>> = BTreeMap::new();
+    for (root, parts) in &split_lines {
+        for (i, part) in parts.iter().enumerate() {
+            if let Part::Component(component) = part {
+                siblings
+                    .entry((*root, &parts[..i]))
+                    .or_default()
+                    .insert(component.as_ref());
+            }
+        }
+    }
+
+    lines
+        .iter()
+        .zip(&split_lines)
+        .map(|(line, (root, parts))| {
+            // the unique prefixes stand in for the strategy's abbreviations
+            let abbreviations = parts
+                .iter()
+                .enumerate()
+                .map(|(i, part)| {
+                    let Part::Component(component) = part else {
+                        return None;
+                    };
+                    let others: Vec<&str> = siblings[&(*root, &parts[..i])]
+                        .iter()
+                        .filter(|s| **s != component.as_ref())
+                        .copied()
+                        .collect();
+                    // unescaped components aren't borrowed from the line, and neither are their
+                    // prefixes
+                    match component {
+                        Cow::Borrowed(component) => {
+                            Some(unique_abbreviation(cfg, component, &others))
+                        }
+                        Cow::Owned(component) => Some(Cow::Owned(
+                            unique_abbreviation(cfg, component, &others).into_owned(),
+                        )),
+                    }
+                })
+                .collect();
+
+            shorten_components_with(cfg, line.as_ref(), abbreviations)
+                .map(Cow::into_owned)
+                .collect()
+        })
+        .collect()
+}
+
+/// Abbreviates `component` to its shortest prefix that none of `others` (its siblings) starts
+/// with, keeping its extension and leading dot if the config is filename aware.
+fn unique_abbreviation<'c>(cfg: &Config, component: &'c str, others: &[&str]) -> Cow<'c, str> {
+    if cfg.filename_aware {
+        let others: Vec<&str> = others.iter().map(|o| filename::split(o).1).collect();
+        filename::abbreviate(component, |stem| {
+            Cow::Borrowed(unique_prefix(stem, &others))
+        })
+    } else {
+        Cow::Borrowed(unique_prefix(component, others))
+    }
+}
+
+/// Returns the shortest prefix of `component` that none of `others` starts with, falling back to
+/// the whole component if there isn't one.
+fn unique_prefix<'c>(component: &'c str, others: &[&str]) -> &'c str {
+    units::prefixes(component)
+        .find(|prefix| !others.iter().any(|o| o.starts_with(prefix)))
+        .unwrap_or(component)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Alias, Collapse, Elision, EmptyComponents, Prefix};
+    use alloc::string::ToString;
+    use alloc::vec;
+    use insta::assert_yaml_snapshot;
+
+    #[test]
+    fn shorten_lines_unique_disambiguates_siblings() {
+        // GIVEN
+        let lines = [
+            "src/main/scala/admin/billing/Server.scala",
+            "src/main/scala/admin/backup/Server.scala",
+            "src/main/scala/admin/backup/Components.scala",
+            "src/test/scala/admin/billing/ServerSpec.scala",
+        ];
+
+        // WHEN
+        let result = shorten_lines_unique(&Config::default(), &lines);
+
+        // THEN
+        assert_yaml_snapshot!(result, @r"
+        - - s
+          - m
+          - s
+          - a
+          - bi
+          - Server.scala
+        - - s
+          - m
+          - s
+          - a
+          - ba
+          - Server.scala
+        - - s
+          - m
+          - s
+          - a
+          - ba
+          - Components.scala
+        - - s
+          - t
+          - s
+          - a
+          - b
+          - ServerSpec.scala
+        ");
+    }
+
+    #[test]
+    fn shorten_lines_unique_only_compares_components_with_the_same_parent() {
+        // GIVEN
+        let lines = ["api/billing/a.rs", "web/backup/b.rs"];
+
+        // WHEN
+        let result = shorten_lines_unique(&Config::default(), &lines);
+
+        // THEN
+        assert_yaml_snapshot!(result, @r"
+        - - a
+          - b
+          - a.rs
+        - - w
+          - b
+          - b.rs
+        ");
+    }
+
+    #[test]
+    fn shorten_lines_unique_keeps_components_that_prefix_a_sibling() {
+        // GIVEN
+        let lines = ["bill/a.rs", "billing/b.rs", "ñandú/c.rs", "ñandúes/d.rs"];
+
+        // WHEN
+        let result = shorten_lines_unique(&Config::default(), &lines);
+
+        // THEN
+        assert_yaml_snapshot!(result, @r"
+        - - bill
+          - a.rs
+        - - billi
+          - b.rs
+        - - ñandú
+          - c.rs
+        - - ñandúe
+          - d.rs
+        ");
+    }
+
+    #[test]
+    fn shorten_lines_unique_respects_config() {
+        // GIVEN
+        let lines = [
+            "/src/main/scala/Server.scala",
+            "/src/test/scala/ServerSpec.scala",
+        ];
+        let cfg = Config {
+            ignore_first_n: 2,
+            ..Config::default()
+        };
+
+        // WHEN
+        let result = shorten_lines_unique(&cfg, &lines);
+
+        // THEN
+        assert_yaml_snapshot!(result, @r#"
+        - - ""
+          - src
+          - m
+          - s
+          - Server.scala
+        - - ""
+          - src
+          - t
+          - s
+          - ServerSpec.scala
+        "#);
+    }
+
+    #[test]
+    fn shorten_lines_unique_respects_max_width() {
+        // GIVEN
+        let lines = [
+            "src/main/scala/admin/billing/Server.scala",
+            "src/main/scala/admin/backup/Server.scala",
+        ];
+        let cfg = Config {
+            max_width: Some(32),
+            ..Config::default()
+        };
+
+        // WHEN
+        let result = shorten_lines_unique(&cfg, &lines);
+
+        // THEN
+        assert_yaml_snapshot!(result, @r"
+        - - s
+          - m
+          - s
+          - admin
+          - billing
+          - Server.scala
+        - - s
+          - m
+          - s
+          - admin
+          - backup
+          - Server.scala
+        ");
+    }
+
+    #[cfg(feature = "graphemes")]
+    #[test]
+    fn shorten_lines_unique_does_not_split_grapheme_clusters() {
+        // GIVEN
+        let lines = [
+            "e\u{301}cole/a.rs",
+            "e\u{301}tude/b.rs",
+            "\u{1112}\u{1161}\u{11ab}\u{1100}\u{116e}\u{11a8}/c.rs",
+            "\u{1112}\u{1161}\u{11ab}\u{1100}\u{1173}\u{11af}/d.rs",
+        ];
+
+        // WHEN
+        let result = shorten_lines_unique(&Config::default(), &lines);
+
+        // THEN
+        assert_yaml_snapshot!(result, @r"
+        - - éc
+          - a.rs
+        - - ét
+          - b.rs
+        - - 한국
+          - c.rs
+        - - 한글
+          - d.rs
+        ");
+    }
+
+    #[test]
+    fn shorten_lines_unique_applies_rules() {
+        // GIVEN
+        let lines = [
+            "src/main/scala/billing/Server.scala",
+            "src/main/scala/backup/Server.scala",
+        ];
+        let cfg = Config {
+            rules: vec![
+                "word:src => drop".parse().unwrap(),
+                "word:scala => alias:sc".parse().unwrap(),
+                "word:main => strategy:first:2".parse().unwrap(),
+            ],
+            ..Config::default()
+        };
+
+        // WHEN
+        let result = shorten_lines_unique(&cfg, &lines);
+
+        // THEN
+        assert_yaml_snapshot!(result, @r"
+        - - ma
+          - sc
+          - bi
+          - Server.scala
+        - - ma
+          - sc
+          - ba
+          - Server.scala
+        ");
+    }
+
+    #[test]
+    fn shorten_lines_unique_compares_stems_if_filename_aware() {
+        // GIVEN
+        let lines = ["src/Server.scala", "src/ServerSpec.scala", "src/.settings"];
+        let cfg = Config {
+            ignore_last_n: 0,
+            filename_aware: true,
+            ..Config::default()
+        };
+
+        // WHEN
+        let result = shorten_lines_unique(&cfg, &lines);
+
+        // THEN
+        assert_yaml_snapshot!(result, @r#"
+        - - s
+          - Server.scala
+        - - s
+          - ServerS.scala
+        - - s
+          - ".s"
+        "#);
+    }
+
+    #[test]
+    fn shorten_lines_unique_applies_aliases() {
+        // GIVEN
+        let lines = [
+            "src/main/scala/billing/Server.scala",
+            "src/test/scala/billing/ServerSpec.scala",
+            "src/main/scala/backup/Server.scala",
+        ];
+        let cfg = Config {
+            aliases: vec![Alias::new(["src", "main", "scala"], "main")],
+            ..Config::default()
+        };
+
+        // WHEN
+        let result = shorten_lines_unique(&cfg, &lines);
+
+        // THEN
+        assert_yaml_snapshot!(result, @r"
+        - - main
+          - bi
+          - Server.scala
+        - - s
+          - t
+          - s
+          - b
+          - ServerSpec.scala
+        - - main
+          - ba
+          - Server.scala
+        ");
+    }
+
+    #[test]
+    fn shorten_lines_unique_substitutes_prefixes() {
+        // GIVEN
+        let lines = [
+            "/home/alice/billing/Server.scala",
+            "/home/alice/backup/Server.scala",
+            "/home/alicia/backup/Server.scala",
+        ];
+        let cfg = Config {
+            prefixes: vec![Prefix::new("/home/alice", "~")],
+            ..Config::default()
+        };
+
+        // WHEN
+        let result = shorten_lines_unique(&cfg, &lines);
+
+        // THEN
+        assert_yaml_snapshot!(result, @r#"
+        - - "~"
+          - bi
+          - Server.scala
+        - - "~"
+          - ba
+          - Server.scala
+        - - ""
+          - h
+          - a
+          - b
+          - Server.scala
+        "#);
+    }
+
+    #[test]
+    fn shorten_lines_unique_collapses_repeated_components() {
+        // GIVEN
+        let lines = [
+            "build/build/billing/Server.scala",
+            "build/build/backup/Server.scala",
+        ];
+        let cfg = Config {
+            collapse: Collapse::Runs,
+            ..Config::default()
+        };
+
+        // WHEN
+        let result = shorten_lines_unique(&cfg, &lines);
+
+        // THEN
+        assert_yaml_snapshot!(result, @r"
+        - - b×2
+          - bi
+          - Server.scala
+        - - b×2
+          - ba
+          - Server.scala
+        ");
+    }
+
+    #[test]
+    fn shorten_lines_unique_elides_middle_components() {
+        // GIVEN
+        let lines = [
+            "src/main/scala/admin/billing/Server.scala",
+            "src/main/scala/admin/backup/Server.scala",
+            "src/test/Server.scala",
+        ];
+        let cfg = Config {
+            elision: Some(Elision {
+                keep_first: 1,
+                keep_last: 2,
+                marker: "…({count})".to_string(),
+            }),
+            ..Config::default()
+        };
+
+        // WHEN
+        let result = shorten_lines_unique(&cfg, &lines);
+
+        // THEN
+        assert_yaml_snapshot!(result, @r"
+        - - s
+          - …(3)
+          - bi
+          - Server.scala
+        - - s
+          - …(3)
+          - ba
+          - Server.scala
+        - - s
+          - t
+          - Server.scala
+        ");
+    }
+
+    #[test]
+    fn shorten_lines_unique_only_compares_components_under_the_same_root() {
+        // GIVEN
+        let lines = ["/usr/local/bin", "/usr//lib/", "usr/libexec/tool"];
+        let cfg = Config {
+            empty_components: EmptyComponents::Drop,
+            absolute_root: true,
+            ..Config::default()
+        };
+
+        // WHEN
+        let result = shorten_lines_unique(&cfg, &lines);
+
+        // THEN
+        assert_yaml_snapshot!(result, @r#"
+        - - ""
+          - u
+          - lo
+          - bin
+        - - ""
+          - u
+          - lib
+        - - u
+          - l
+          - tool
+        "#);
+    }
+}
@@ -0,0 +1,88 @@
+//! Helpers for slicing components into user-perceived characters.
+//!
+//! With the `graphemes` feature (enabled by default) a "unit" is an extended grapheme cluster,
+//! so combining sequences, Hangul jamo and emoji ZWJ sequences are never split apart. Without
+//! it, a unit is a `char`.
+
+/// Returns the byte offsets at which each unit of `s` starts.
+#[cfg(feature = "graphemes")]
+pub(crate) fn unit_starts(s: &str) -> impl DoubleEndedIterator<Item = usize> + '_ {
+    use unicode_segmentation::UnicodeSegmentation;
+
+    s.grapheme_indices(true).map(|(i, _)| i)
+}
+
+/// Returns the byte offsets at which each unit of `s` starts.
+#[cfg(not(feature = "graphemes"))]
+pub(crate) fn unit_starts(s: &str) -> impl DoubleEndedIterator<Item = usize> + '_ {
+    s.char_indices().map(|(i, _)| i)
+}
+
+/// Returns the first `n` units of `s`.
+pub(crate) fn first_units(s: &str, n: usize) -> &str {
+    let end = unit_starts(s).nth(n).unwrap_or(s.len());
+    &s[..end]
+}
+
+/// Returns the last `n` units of `s`.
+pub(crate) fn last_units(s: &str, n: usize) -> &str {
+    let start = match n.checked_sub(1) {
+        Some(skip) => unit_starts(s).rev().nth(skip).unwrap_or(0),
+        None => s.len(),
+    };
+    &s[start..]
+}
+
+/// Returns every non-empty prefix of `s` that ends on a unit boundary, shortest first.
+pub(crate) fn prefixes(s: &str) -> impl Iterator<Item = &str> {
+    unit_starts(s)
+        .skip(1)
+        .chain(core::iter::once(s.len()))
+        .map(|end| &s[..end])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+    use alloc::vec::Vec;
+
+    #[test]
+    fn first_and_last_units_work_for_ascii() {
+        // GIVEN
+        let s = "service";
+
+        // WHEN
+        // THEN
+        assert_eq!(first_units(s, 2), "se");
+        assert_eq!(first_units(s, 10), "service");
+        assert_eq!(last_units(s, 2), "ce");
+        assert_eq!(last_units(s, 10), "service");
+        assert_eq!(last_units(s, 0), "");
+    }
+
+    #[test]
+    fn prefixes_works() {
+        // GIVEN
+        let s = "ñan";
+
+        // WHEN
+        let result: Vec<_> = prefixes(s).collect();
+
+        // THEN
+        assert_eq!(result, vec!["ñ", "ña", "ñan"]);
+    }
+
+    #[cfg(feature = "graphemes")]
+    #[test]
+    fn units_are_grapheme_clusters() {
+        // GIVEN
+        let s = "e\u{301}te\u{301}";
+
+        // WHEN
+        // THEN
+        assert_eq!(first_units(s, 1), "e\u{301}");
+        assert_eq!(last_units(s, 1), "e\u{301}");
+        assert_eq!(prefixes(s).count(), 3);
+    }
+}
@@ -0,0 +1,198 @@
+use alloc::vec::Vec;
+use unicode_width::UnicodeWidthStr;
+
+/// Returns the number of terminal columns `s` occupies.
+pub(crate) fn display_width(s: &str) -> usize {
+    s.width()
+}
+
+/// The width of a component, as determined before shortening the line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Measure {
+    /// The component is left out of the line
+    Dropped,
+    /// The component takes up `width` columns, and `abbreviated` columns if it's allowed to be
+    /// abbreviated
+    Kept {
+        width: usize,
+        abbreviated: Option<usize>,
+    },
+}
+
+impl Measure {
+    /// Measures a component that's to be output as `component`, and possibly abbreviated to
+    /// `abbreviated`.
+    pub(crate) fn of(component: &str, abbreviated: Option<&str>) -> Self {
+        Measure::Kept {
+            width: display_width(component),
+            abbreviated: abbreviated.map(display_width),
+        }
+    }
+}
+
+/// Determines how many components need to be considered for abbreviation (starting from the
+/// first one) for the joined line to fit within `max_width` columns.
+///
+/// `components` yields each component along with the delimiter that followed it, which is
+/// replaced by `output_delimiter` (if provided) when measuring. `measure` is called with the
+/// index of each component and the component itself, and returns its [`Measure`]. Delimiters next to
+/// dropped components are not counted. The returned cutoff is exclusive; if the line doesn't fit
+/// even after abbreviating every component, `usize::MAX` is returned.
+pub(crate) fn width_cutoff<'d, C, I, F>(
+    max_width: usize,
+    output_delimiter: Option<&str>,
+    components: I,
+    mut measure: F,
+) -> usize
+where
+    I: Iterator<Item = (C, Option<&'d str>)>,
+    F: FnMut(usize, C) -> Measure,
+{
+    let measures: Vec<_> = components
+        .enumerate()
+        .filter_map(|(i, (component, delimiter))| match measure(i, component) {
+            Measure::Dropped => None,
+            Measure::Kept { width, abbreviated } => Some((i, width, abbreviated, delimiter)),
+        })
+        .collect();
+
+    let mut width = measures.iter().map(|(_, w, _, _)| w).sum::<usize>()
+        + measures
+            .iter()
+            .rev()
+            .skip(1)
+            .map(|(_, _, _, delimiter)| {
+                delimiter.map_or(0, |d| display_width(output_delimiter.unwrap_or(d)))
+            })
+            .sum::<usize>();
+
+    if width <= max_width {
+        return 0;
+    }
+
+    for (i, w, abbreviated, _) in measures {
+        if let Some(abbreviated) = abbreviated {
+            width = width - w + abbreviated;
+        }
+
+        if width <= max_width {
+            return i + 1;
+        }
+    }
+
+    usize::MAX
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Delimiter;
+    use alloc::borrow::Cow;
+
+    fn first_char(_: usize, c: Cow<'_, str>) -> Measure {
+        Measure::of(&c, Some(&c[..c.chars().next().map_or(0, char::len_utf8)]))
+    }
+
+    #[test]
+    fn display_width_counts_wide_characters_as_two_columns() {
+        // GIVEN
+        // WHEN
+        // THEN
+        assert_eq!(display_width("module"), 6);
+        assert_eq!(display_width("模块"), 4);
+        assert_eq!(display_width("🦀"), 2);
+        assert_eq!(display_width("e\u{301}"), 1);
+    }
+
+    #[test]
+    fn width_cutoff_is_zero_when_line_already_fits() {
+        // GIVEN
+        let delimiter = Delimiter::from("/");
+        let components = delimiter.split("module/lib.rs");
+
+        // WHEN
+        let result = width_cutoff(13, None, components, first_char);
+
+        // THEN
+        assert_eq!(result, 0);
+    }
+
+    #[test]
+    fn width_cutoff_abbreviates_only_as_many_components_as_needed() {
+        // GIVEN
+        let delimiter = Delimiter::from("/");
+        let components = delimiter.split("module/submodule/service/lib.rs");
+
+        // WHEN
+        let result = width_cutoff(20, None, components, first_char);
+
+        // THEN
+        assert_eq!(result, 2);
+    }
+
+    #[test]
+    fn width_cutoff_skips_components_that_cannot_be_shortened() {
+        // GIVEN
+        let delimiter = Delimiter::from("/");
+        let components = delimiter.split("module/submodule/service/lib.rs");
+
+        // WHEN
+        let result = width_cutoff(20, None, components, |i, c| {
+            if i == 0 {
+                Measure::of(&c, None)
+            } else {
+                first_char(i, c)
+            }
+        });
+
+        // THEN
+        assert_eq!(result, 3);
+    }
+
+    #[test]
+    fn width_cutoff_is_max_when_line_cannot_fit() {
+        // GIVEN
+        let delimiter = Delimiter::from("/");
+        let components = delimiter.split("module/submodule/lib.rs");
+
+        // WHEN
+        let result = width_cutoff(4, None, components, first_char);
+
+        // THEN
+        assert_eq!(result, usize::MAX);
+    }
+
+    #[test]
+    fn width_cutoff_measures_output_delimiter_if_provided() {
+        // GIVEN
+        let delimiter = Delimiter::from("/");
+        let components = delimiter.split("module/submodule/lib.rs");
+
+        // WHEN
+        let with_original = width_cutoff(23, None, components.clone(), first_char);
+        let with_output = width_cutoff(23, Some(" / "), components, first_char);
+
+        // THEN
+        assert_eq!(with_original, 0);
+        assert_eq!(with_output, 1);
+    }
+
+    #[test]
+    fn width_cutoff_does_not_count_dropped_components() {
+        // GIVEN
+        let delimiter = Delimiter::from("/");
+        let components = delimiter.split("module/submodule/lib.rs");
+
+        // WHEN
+        let result = width_cutoff(14, None, components, |i, c| {
+            if i == 1 {
+                Measure::Dropped
+            } else {
+                first_char(i, c)
+            }
+        });
+
+        // THEN
+        assert_eq!(result, 0);
+    }
+}